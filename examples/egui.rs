@@ -10,12 +10,14 @@ fn main() {
     raylib::InitWindow(SCREEN_WIDTH as i32, SCREEN_HEIGHT as i32, TITLE);
     raylib::SetTargetFPS(60);
 
-    let mut egui_raylib = EguiRaylib::new();
+    let mut egui_raylib = EguiRaylib::builder()
+        .visuals(egui::Visuals::light())
+        .build();
 
     #[cfg(not(target_arch = "wasm32"))]
     {
         let mut age = 1;
-        while !raylib::WindowShouldClose() {
+        while !egui_raylib.should_close() {
             use raylib_egui_rs::color::Color;
 
             raylib::BeginDrawing();
@@ -23,14 +25,13 @@ fn main() {
 
             egui_raylib.run(|egui_ctx| {
                 egui::Window::new("UI").show(&egui_ctx, |ui| {
-                    ui.visuals_mut().dark_mode = false;
                     ui.label("XXX");
                     ui.label("a very nice gui :3");
                     if ui.button("print \"hello world\"").clicked() {
                         println!("hello world");
                     }
                     if ui.button("quit").clicked() {
-                        // ctx.request_quit();
+                        egui_ctx.send_viewport_cmd(egui::ViewportCommand::Close);
                     }
                     ui.image(egui::include_image!("ferris.png"));
                 });
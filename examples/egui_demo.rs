@@ -1,6 +1,7 @@
 use egui_demo_lib::DemoWindows;
 use raylib_egui_rs::egui::EguiRaylib;
 use raylib_egui_rs::raylib;
+use std::time::Duration;
 
 const SCREEN_WIDTH: usize = 1280;
 const SCREEN_HEIGHT: usize = 720;
@@ -30,7 +31,8 @@ fn main() {
             });
 
             egui_raylib.paint();
-            raylib::EndDrawing();
+            // input waits at most this long while egui sleeps towards its next repaint
+            egui_raylib.end_drawing_reactive(Duration::from_millis(100));
         }
         raylib::CloseWindow();
     }
@@ -0,0 +1,135 @@
+// buffers.rs
+
+use crate::raylib::Shader;
+use crate::rlgl;
+use crate::rlgl::rlShaderLocationIndex;
+use std::mem::{offset_of, size_of};
+
+/// Vertex layout streamed to the GPU: interleaved position, uv and color.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub(crate) struct Vertex {
+    pub pos: [f32; 2],
+    pub uv: [f32; 2],
+    pub color: [u8; 4],
+}
+
+/// A vertex and an index buffer that live as long as the painter.
+///
+/// Every frame the whole egui output is written into them with one upload each,
+/// they only get reallocated when a frame does not fit anymore.
+pub(crate) struct StreamBuffers {
+    vao_id: u32,
+    vbo_id: u32,
+    ibo_id: u32,
+    vertex_capacity: usize,
+    index_capacity: usize,
+}
+
+impl StreamBuffers {
+    /// Create the vertex array, the buffers follow with the first upload.
+    pub fn new() -> Self {
+        Self {
+            // zero if the platform has no vertex arrays, then the buffers are bound directly
+            vao_id: rlgl::load_vertex_array(),
+            vbo_id: 0,
+            ibo_id: 0,
+            vertex_capacity: 0,
+            index_capacity: 0,
+        }
+    }
+
+    /// Upload the frame data, growing the GPU buffers when they are too small.
+    pub fn upload(&mut self, vertices: &[Vertex], indices: &[u16]) {
+        if vertices.len() > self.vertex_capacity {
+            self.vertex_capacity = vertices.len().next_power_of_two();
+            if self.vbo_id != 0 {
+                rlgl::unload_vertex_buffer(self.vbo_id);
+            }
+            self.vbo_id =
+                rlgl::allocate_vertex_buffer(self.vertex_capacity * size_of::<Vertex>(), true);
+        }
+        if indices.len() > self.index_capacity {
+            self.index_capacity = indices.len().next_power_of_two();
+            if self.ibo_id != 0 {
+                rlgl::unload_vertex_buffer(self.ibo_id);
+            }
+            self.ibo_id =
+                rlgl::allocate_vertex_buffer_element(self.index_capacity * size_of::<u16>(), true);
+        }
+        rlgl::update_vertex_buffer(self.vbo_id, vertices, 0);
+        rlgl::update_vertex_buffer_elements(self.ibo_id, indices, 0);
+    }
+
+    /// Bind the buffers for drawing with `shader`, vertex attributes start at `first_vertex`.
+    pub fn bind(&self, shader: &Shader, first_vertex: usize) {
+        rlgl::enable_vertex_array(self.vao_id);
+        rlgl::enable_vertex_buffer(self.vbo_id);
+        rlgl::enable_vertex_buffer_element(self.ibo_id);
+
+        let stride = size_of::<Vertex>();
+        let base = first_vertex * stride;
+        for (loc, comp_size, attrib_type, normalized, offset) in [
+            (
+                rlShaderLocationIndex::RL_SHADER_LOC_VERTEX_POSITION,
+                2,
+                rlgl::FLOAT,
+                false,
+                offset_of!(Vertex, pos),
+            ),
+            (
+                rlShaderLocationIndex::RL_SHADER_LOC_VERTEX_TEXCOORD01,
+                2,
+                rlgl::FLOAT,
+                false,
+                offset_of!(Vertex, uv),
+            ),
+            (
+                rlShaderLocationIndex::RL_SHADER_LOC_VERTEX_COLOR,
+                4,
+                rlgl::UNSIGNED_BYTE,
+                true,
+                offset_of!(Vertex, color),
+            ),
+        ] {
+            let index = shader_location(shader, loc);
+            if index < 0 {
+                continue;
+            }
+            rlgl::set_vertex_attribute(
+                index as u32,
+                comp_size,
+                attrib_type,
+                normalized,
+                stride as i32,
+                (base + offset) as i32,
+            );
+            rlgl::enable_vertex_attribute(index as u32);
+        }
+    }
+
+    pub fn unbind(&self) {
+        rlgl::disable_vertex_array();
+        rlgl::disable_vertex_buffer();
+        rlgl::disable_vertex_buffer_element();
+    }
+
+    pub fn destroy(&mut self) {
+        if self.vbo_id != 0 {
+            rlgl::unload_vertex_buffer(self.vbo_id);
+        }
+        if self.ibo_id != 0 {
+            rlgl::unload_vertex_buffer(self.ibo_id);
+        }
+        if self.vao_id != 0 {
+            rlgl::unload_vertex_array(self.vao_id);
+        }
+        (self.vao_id, self.vbo_id, self.ibo_id) = (0, 0, 0);
+        (self.vertex_capacity, self.index_capacity) = (0, 0);
+    }
+}
+
+/// Look up one of the shader's standard locations, -1 if the shader does not use it.
+pub(crate) fn shader_location(shader: &Shader, index: rlShaderLocationIndex) -> i32 {
+    unsafe { *shader.locs.add(index as usize) }
+}
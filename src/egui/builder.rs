@@ -0,0 +1,144 @@
+// builder.rs
+
+use std::sync::Arc;
+#[cfg(feature = "persistence")]
+use std::time::Duration;
+
+#[cfg(feature = "persistence")]
+use super::Storage;
+use super::{EguiRaylib, RaylibImageLoader};
+
+/// How often egui's memory is saved when nothing else is set.
+#[cfg(feature = "persistence")]
+const DEFAULT_AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Configures an [`EguiRaylib`] before it is created.
+///
+/// Everything here can also be changed later through [`EguiRaylib::ctx`], the builder only
+/// makes sure the first frame already looks right.
+pub struct EguiRaylibBuilder {
+    style: Option<egui::Style>,
+    visuals: Option<egui::Visuals>,
+    fonts: Option<egui::FontDefinitions>,
+    zoom_factor: Option<f32>,
+    pixels_per_point: Option<f32>,
+    image_loaders: bool,
+    #[cfg(feature = "persistence")]
+    storage: Option<Box<dyn Storage>>,
+    #[cfg(feature = "persistence")]
+    autosave_interval: Duration,
+}
+
+impl Default for EguiRaylibBuilder {
+    fn default() -> Self {
+        Self {
+            style: None,
+            visuals: None,
+            fonts: None,
+            zoom_factor: None,
+            pixels_per_point: None,
+            image_loaders: true,
+            #[cfg(feature = "persistence")]
+            storage: None,
+            #[cfg(feature = "persistence")]
+            autosave_interval: DEFAULT_AUTOSAVE_INTERVAL,
+        }
+    }
+}
+
+impl EguiRaylibBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The initial style, applied before [`Self::visuals`].
+    pub fn style(mut self, style: egui::Style) -> Self {
+        self.style = Some(style);
+        self
+    }
+
+    /// The initial visuals, like `egui::Visuals::light()`.
+    pub fn visuals(mut self, visuals: egui::Visuals) -> Self {
+        self.visuals = Some(visuals);
+        self
+    }
+
+    pub fn fonts(mut self, fonts: egui::FontDefinitions) -> Self {
+        self.fonts = Some(fonts);
+        self
+    }
+
+    /// Scale the whole ui on top of the monitor DPI, egui's `ctrl +` and `ctrl -` change it too.
+    pub fn zoom_factor(mut self, zoom_factor: f32) -> Self {
+        self.zoom_factor = Some(zoom_factor);
+        self
+    }
+
+    /// Use a fixed number of pixels per point instead of following the monitor DPI.
+    pub fn pixels_per_point(mut self, pixels_per_point: f32) -> Self {
+        self.pixels_per_point = Some(pixels_per_point);
+        self
+    }
+
+    /// Install the `egui_extras` image loaders and a [`RaylibImageLoader`] so
+    /// `egui::include_image!` and image uris work, on by default.
+    ///
+    /// The raylib loader goes last, egui asks it first, so images decode the same way as
+    /// in the game.
+    pub fn image_loaders(mut self, image_loaders: bool) -> Self {
+        self.image_loaders = image_loaders;
+        self
+    }
+
+    /// Restore egui's memory from `storage` and save it back there, every
+    /// [`Self::autosave_interval`] and when the `EguiRaylib` is dropped.
+    ///
+    /// Window positions, collapsed headers and the zoom survive a restart, use
+    /// `FileStorage::new(".")` to keep them next to the game.
+    #[cfg(feature = "persistence")]
+    pub fn storage(mut self, storage: impl Storage + 'static) -> Self {
+        self.storage = Some(Box::new(storage));
+        self
+    }
+
+    /// How often the memory is saved to the [`Self::storage`], 30 seconds by default.
+    #[cfg(feature = "persistence")]
+    pub fn autosave_interval(mut self, autosave_interval: Duration) -> Self {
+        self.autosave_interval = autosave_interval;
+        self
+    }
+
+    pub fn build(self) -> EguiRaylib {
+        let mut egui_raylib = EguiRaylib::empty();
+        #[cfg(feature = "persistence")]
+        if let Some(storage) = self.storage {
+            // before the fonts and styles below, restoring replaces all of the memory
+            if let Err(err) = super::load_memory(&egui_raylib.ctx, storage.as_ref()) {
+                super::storage::log_warning(&err);
+            }
+            egui_raylib.storage = Some(storage);
+            egui_raylib.autosave_interval = self.autosave_interval;
+        }
+        let ctx = &egui_raylib.ctx;
+        if let Some(style) = self.style {
+            ctx.set_style(style);
+        }
+        if let Some(visuals) = self.visuals {
+            ctx.set_visuals(visuals);
+        }
+        if let Some(fonts) = self.fonts {
+            ctx.set_fonts(fonts);
+        }
+        if let Some(zoom_factor) = self.zoom_factor {
+            ctx.set_zoom_factor(zoom_factor);
+        }
+        if self.image_loaders {
+            egui_extras::install_image_loaders(ctx);
+            ctx.add_image_loader(Arc::new(RaylibImageLoader::default()));
+        }
+        egui_raylib
+            .input
+            .set_pixels_per_point(self.pixels_per_point);
+        egui_raylib
+    }
+}
@@ -0,0 +1,38 @@
+// egui fragment shader, the version header is added when loading
+
+#if NEW_SHADER_INTERFACE
+#define I in
+#define texture2D texture
+out vec4 finalColor;
+#else
+#define I varying
+#define finalColor gl_FragColor
+#endif
+
+I vec2 fragTexCoord;
+I vec4 fragColor;
+
+uniform sampler2D texture0;
+// raylib textures have straight alpha, the ones egui uploads are premultiplied
+uniform int premultiplyTexture;
+// set when the framebuffer wants linear colors, like an sRGB framebuffer
+uniform int linearOutput;
+
+vec3 linear_from_gamma(vec3 srgb) {
+    vec3 lower = srgb / vec3(12.92);
+    vec3 higher = pow((srgb + vec3(0.055)) / vec3(1.055), vec3(2.4));
+    return mix(higher, lower, vec3(lessThan(srgb, vec3(0.04045))));
+}
+
+void main() {
+    vec4 texel = texture2D(texture0, fragTexCoord);
+    if (premultiplyTexture != 0) {
+        texel.rgb *= texel.a;
+    }
+    // egui blends in gamma space with premultiplied colors
+    vec4 color = fragColor * texel;
+    if (linearOutput != 0) {
+        color.rgb = linear_from_gamma(color.rgb);
+    }
+    finalColor = color;
+}
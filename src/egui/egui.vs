@@ -0,0 +1,24 @@
+// egui vertex shader, the version header is added when loading
+
+#if NEW_SHADER_INTERFACE
+#define I in
+#define O out
+#else
+#define I attribute
+#define O varying
+#endif
+
+I vec2 vertexPosition;
+I vec2 vertexTexCoord;
+I vec4 vertexColor;
+
+uniform mat4 mvp;
+
+O vec2 fragTexCoord;
+O vec4 fragColor;
+
+void main() {
+    fragTexCoord = vertexTexCoord;
+    fragColor = vertexColor;
+    gl_Position = mvp * vec4(vertexPosition, 0.0, 1.0);
+}
@@ -0,0 +1,94 @@
+// fonts.rs
+
+use std::sync::Arc;
+
+use crate::raylib;
+use egui::{FontData, FontDefinitions, FontFamily};
+
+/// Builds `egui::FontDefinitions` from font files, so egui can use the game's fonts.
+///
+/// It starts from egui's default fonts, they stay around as fallbacks for emoji and
+/// symbols. Pass the result to `EguiRaylibBuilder::fonts` or `egui::Context::set_fonts`.
+/// ```rust,no_run
+/// use egui::FontFamily;
+/// use raylib_egui_rs::egui::FontsBuilder;
+///
+/// let fonts = FontsBuilder::new()
+///     .add_font_file("body", "assets/body.ttf")?
+///     .add_font_file("cjk", "assets/NotoSansJP-Regular.otf")?
+///     .family(FontFamily::Proportional, &["body", "cjk"])
+///     .family(FontFamily::Name("title".into()), &["body"])
+///     .build();
+/// # Ok::<(), String>(())
+/// ```
+#[derive(Default)]
+pub struct FontsBuilder {
+    fonts: FontDefinitions,
+}
+
+impl FontsBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start from nothing instead of egui's default fonts.
+    pub fn empty() -> Self {
+        Self {
+            fonts: FontDefinitions::empty(),
+        }
+    }
+
+    /// Add a TTF or OTF font under `name`, for example bytes from `raylib::LoadFileData`.
+    ///
+    /// The font is only used once it is part of a family, see [`Self::family`].
+    pub fn add_font_data(mut self, name: &str, data: Vec<u8>) -> Self {
+        self.fonts
+            .font_data
+            .insert(name.to_owned(), Arc::new(FontData::from_owned(data)));
+        self
+    }
+
+    /// Read a TTF or OTF file with raylib and add it under `name`.
+    pub fn add_font_file(self, name: &str, file_name: &str) -> Result<Self, String> {
+        let data = raylib::LoadFileData(file_name)
+            .ok_or_else(|| format!("failed to load font file {file_name}"))?;
+        let bytes = data.to_vec();
+        raylib::UnloadFileData(data.as_mut_ptr());
+        Ok(self.add_font_data(name, bytes))
+    }
+
+    /// Make `family` use `fonts`, the first one that has a glyph wins.
+    ///
+    /// egui's fonts for the family stay behind them as fallbacks, a new named family gets
+    /// the ones of `FontFamily::Proportional`.
+    pub fn family(mut self, family: FontFamily, fonts: &[&str]) -> Self {
+        let fallbacks = self
+            .fonts
+            .families
+            .get(&family)
+            .or_else(|| self.fonts.families.get(&FontFamily::Proportional))
+            .cloned()
+            .unwrap_or_default();
+        let mut list: Vec<String> = fonts.iter().map(|&name| name.to_owned()).collect();
+        list.extend(
+            fallbacks
+                .into_iter()
+                .filter(|name| !fonts.contains(&name.as_str())),
+        );
+        self.fonts.families.insert(family, list);
+        self
+    }
+
+    /// Add `font` as the last fallback of `family`, for example for CJK or emoji glyphs.
+    pub fn fallback(mut self, family: FontFamily, font: &str) -> Self {
+        let list = self.fonts.families.entry(family).or_default();
+        if !list.iter().any(|name| name == font) {
+            list.push(font.to_owned());
+        }
+        self
+    }
+
+    pub fn build(self) -> FontDefinitions {
+        self.fonts
+    }
+}
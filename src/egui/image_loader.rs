@@ -0,0 +1,337 @@
+// image_loader.rs
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::raylib;
+use crate::raylib::{Image, PixelFormat};
+use egui::load::{BytesPoll, ImageLoadResult, ImageLoader, ImagePoll, LoadError, SizeHint};
+use egui::{ColorImage, FrameDurations};
+
+/// Decoded frames of one uri, a single one for still images.
+type Frames = Result<Vec<Arc<ColorImage>>, String>;
+
+/// An egui image loader that decodes with raylib.
+///
+/// It handles every image format raylib is built with through the `SUPPORT_FILEFORMAT_*`
+/// features, including animated GIFs. The bytes come from egui's bytes loaders, so it works
+/// for `egui::include_image!`, files and anything else egui can fetch.
+#[derive(Default)]
+pub struct RaylibImageLoader {
+    cache: Mutex<HashMap<String, Frames>>,
+}
+
+impl RaylibImageLoader {
+    pub const ID: &'static str = egui::generate_loader_id!(RaylibImageLoader);
+}
+
+impl ImageLoader for RaylibImageLoader {
+    fn id(&self) -> &str {
+        Self::ID
+    }
+
+    fn load(&self, ctx: &egui::Context, uri: &str, _: SizeHint) -> ImageLoadResult {
+        // egui asks for animation frames as `uri#index`
+        let (uri, frame) = match egui::decode_animated_image_uri(uri) {
+            Ok((base, frame)) if file_type(base, None).as_deref() == Some(".gif") => (base, frame),
+            _ => (uri, 0),
+        };
+        if let Some(file_type) = file_type(uri, None)
+            && !is_supported(&file_type)
+        {
+            return Err(LoadError::NotSupported);
+        }
+
+        let mut cache = self.cache.lock().unwrap();
+        if let Some(entry) = cache.get(uri) {
+            return match entry {
+                Ok(frames) => Ok(ImagePoll::Ready {
+                    image: frames[frame % frames.len()].clone(),
+                }),
+                Err(err) => Err(LoadError::Loading(err.clone())),
+            };
+        }
+
+        match ctx.try_load_bytes(uri) {
+            Ok(BytesPoll::Ready { bytes, mime, .. }) => {
+                let Some(file_type) = file_type(uri, mime.as_deref())
+                    .or_else(|| sniff_file_type(&bytes).map(str::to_owned))
+                else {
+                    return Err(LoadError::NotSupported);
+                };
+                if !is_supported(&file_type) {
+                    return Err(LoadError::NotSupported);
+                }
+                let result = if file_type == ".gif" {
+                    decode_gif(&bytes).map(|(frames, durations)| {
+                        ctx.data_mut(|data| data.insert_temp(egui::Id::new(uri), durations));
+                        frames
+                    })
+                } else {
+                    decode_image(&file_type, &bytes).map(|image| vec![Arc::new(image)])
+                };
+                cache.insert(uri.to_owned(), result.clone());
+                match result {
+                    Ok(frames) => Ok(ImagePoll::Ready {
+                        image: frames[frame % frames.len()].clone(),
+                    }),
+                    Err(err) => Err(LoadError::Loading(err)),
+                }
+            }
+            Ok(BytesPoll::Pending { size }) => Ok(ImagePoll::Pending { size }),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn forget(&self, uri: &str) {
+        let uri = egui::decode_animated_image_uri(uri).map_or(uri, |(base, _)| base);
+        self.cache.lock().unwrap().remove(uri);
+    }
+
+    fn forget_all(&self) {
+        self.cache.lock().unwrap().clear();
+    }
+
+    fn byte_size(&self) -> usize {
+        self.cache
+            .lock()
+            .unwrap()
+            .values()
+            .filter_map(|entry| entry.as_ref().ok())
+            .flatten()
+            .map(|image| image.pixels.len() * 4)
+            .sum()
+    }
+}
+
+/// Turn a raylib image into an egui one, the raylib image is unloaded.
+pub(crate) fn color_image_from_raylib(mut image: Image) -> Result<ColorImage, String> {
+    if !raylib::IsImageValid(image) {
+        return Err("raylib could not decode the image".to_owned());
+    }
+    let rgba = PixelFormat::PIXELFORMAT_UNCOMPRESSED_R8G8B8A8 as i32;
+    raylib::ImageFormat(&mut image, rgba);
+    // raylib can't convert compressed formats
+    let result = if image.format == rgba {
+        let size = [image.width as usize, image.height as usize];
+        let pixels =
+            unsafe { std::slice::from_raw_parts(image.data as *const u8, size[0] * size[1] * 4) };
+        Ok(ColorImage::from_rgba_unmultiplied(size, pixels))
+    } else {
+        Err(format!("unsupported pixel format {}", image.format))
+    };
+    raylib::UnloadImage(image);
+    result
+}
+
+fn decode_image(file_type: &str, bytes: &[u8]) -> Result<ColorImage, String> {
+    color_image_from_raylib(raylib::LoadImageFromMemory(file_type, bytes))
+}
+
+fn decode_gif(bytes: &[u8]) -> Result<(Vec<Arc<ColorImage>>, FrameDurations), String> {
+    let (image, frame_count) = raylib::LoadImageAnimFromMemory(".gif", bytes);
+    if !raylib::IsImageValid(image) || frame_count < 1 {
+        return Err("raylib could not decode the gif".to_owned());
+    }
+    // raylib stacks the frames as RGBA8 one after another in the data
+    let size = [image.width as usize, image.height as usize];
+    let frame_len = size[0] * size[1] * 4;
+    let data = unsafe {
+        std::slice::from_raw_parts(image.data as *const u8, frame_len * frame_count as usize)
+    };
+    let frames = data
+        .chunks_exact(frame_len)
+        .map(|pixels| Arc::new(ColorImage::from_rgba_unmultiplied(size, pixels)))
+        .collect::<Vec<_>>();
+    raylib::UnloadImage(image);
+
+    // raylib drops the frame delays, so they are read from the file
+    let mut durations = gif_frame_delays(bytes);
+    durations.resize(frames.len(), DEFAULT_GIF_DELAY);
+    Ok((frames, FrameDurations::new(durations)))
+}
+
+/// What browsers use for frames without a delay.
+const DEFAULT_GIF_DELAY: Duration = Duration::from_millis(100);
+
+/// The delay of every frame, from the graphic control extensions of a gif.
+fn gif_frame_delays(bytes: &[u8]) -> Vec<Duration> {
+    let mut delays = Vec::new();
+    if bytes.len() < 13 {
+        return delays;
+    }
+    // skip the header, the screen descriptor and the global color table
+    let mut pos = 13;
+    if bytes[10] & 0x80 != 0 {
+        pos += 3 << ((bytes[10] & 0x07) + 1);
+    }
+    let mut delay = DEFAULT_GIF_DELAY;
+    while let Some(&block) = bytes.get(pos) {
+        match block {
+            // extension, the graphic control one holds the delay of the next frame
+            0x21 => {
+                if bytes.get(pos + 1) == Some(&0xF9) && bytes.get(pos + 2) == Some(&4) {
+                    let centis = bytes
+                        .get(pos + 4..pos + 6)
+                        .map_or(0, |d| u16::from_le_bytes([d[0], d[1]]));
+                    delay = match centis {
+                        0 | 1 => DEFAULT_GIF_DELAY,
+                        centis => Duration::from_millis(centis as u64 * 10),
+                    };
+                }
+                pos = skip_sub_blocks(bytes, pos + 2);
+            }
+            // image descriptor, optional local color table, then the image data
+            0x2C => {
+                let Some(&packed) = bytes.get(pos + 9) else {
+                    break;
+                };
+                pos += 10;
+                if packed & 0x80 != 0 {
+                    pos += 3 << ((packed & 0x07) + 1);
+                }
+                pos = skip_sub_blocks(bytes, pos + 1);
+                delays.push(delay);
+                delay = DEFAULT_GIF_DELAY;
+            }
+            _ => break,
+        }
+    }
+    delays
+}
+
+/// Skip a chain of gif data sub-blocks starting at `pos`, returns the position after it.
+fn skip_sub_blocks(bytes: &[u8], mut pos: usize) -> usize {
+    while let Some(&len) = bytes.get(pos) {
+        pos += 1 + len as usize;
+        if len == 0 {
+            break;
+        }
+    }
+    pos
+}
+
+/// raylib file type like ".png", from the extension of the uri or else the mime type.
+fn file_type(uri: &str, mime: Option<&str>) -> Option<String> {
+    let name = uri.rsplit(['/', '\\']).next().unwrap_or(uri);
+    match name.rsplit_once('.') {
+        Some((_, extension)) if !extension.is_empty() => {
+            Some(format!(".{}", extension.to_ascii_lowercase()))
+        }
+        _ => {
+            let subtype = mime?.strip_prefix("image/")?;
+            Some(match subtype {
+                "jpeg" => ".jpg".to_owned(),
+                "vnd.radiance" => ".hdr".to_owned(),
+                "vnd-ms.dds" => ".dds".to_owned(),
+                subtype => format!(".{}", subtype.trim_start_matches("x-")),
+            })
+        }
+    }
+}
+
+/// raylib file type from the first bytes of the file, for uris without a hint.
+fn sniff_file_type(bytes: &[u8]) -> Option<&'static str> {
+    [
+        (&b"\x89PNG\r\n\x1a\n"[..], ".png"),
+        (b"GIF87a", ".gif"),
+        (b"GIF89a", ".gif"),
+        (b"\xff\xd8\xff", ".jpg"),
+        (b"qoif", ".qoi"),
+        (b"DDS ", ".dds"),
+        (b"8BPS", ".psd"),
+        (b"#?RADIANCE", ".hdr"),
+        (b"\xabKTX", ".ktx"),
+        (b"PKM ", ".pkm"),
+        (b"BM", ".bmp"),
+    ]
+    .into_iter()
+    .find(|(magic, _)| bytes.starts_with(magic))
+    .map(|(_, file_type)| file_type)
+}
+
+/// Whether raylib was built with a decoder for the file type.
+fn is_supported(file_type: &str) -> bool {
+    match file_type {
+        ".png" => cfg!(feature = "SUPPORT_FILEFORMAT_PNG"),
+        ".bmp" => cfg!(feature = "SUPPORT_FILEFORMAT_BMP"),
+        ".tga" => cfg!(feature = "SUPPORT_FILEFORMAT_TGA"),
+        ".jpg" | ".jpeg" => cfg!(feature = "SUPPORT_FILEFORMAT_JPG"),
+        ".gif" => cfg!(feature = "SUPPORT_FILEFORMAT_GIF"),
+        ".qoi" => cfg!(feature = "SUPPORT_FILEFORMAT_QOI"),
+        ".psd" => cfg!(feature = "SUPPORT_FILEFORMAT_PSD"),
+        ".dds" => cfg!(feature = "SUPPORT_FILEFORMAT_DDS"),
+        ".hdr" => cfg!(feature = "SUPPORT_FILEFORMAT_HDR"),
+        ".pic" => cfg!(feature = "SUPPORT_FILEFORMAT_PIC"),
+        ".ktx" => cfg!(feature = "SUPPORT_FILEFORMAT_KTX"),
+        ".astc" => cfg!(feature = "SUPPORT_FILEFORMAT_ASTC"),
+        ".pkm" => cfg!(feature = "SUPPORT_FILEFORMAT_PKM"),
+        ".pvr" => cfg!(feature = "SUPPORT_FILEFORMAT_PVR"),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 1x1 gif with three frames: 50 ms, a zero delay and no graphic control block.
+    const GIF: &[u8] = &[
+        b'G', b'I', b'F', b'8', b'9', b'a', // header
+        1, 0, 1, 0, 0x80, 0, 0, // screen descriptor, two color global table
+        0, 0, 0, 255, 255, 255, // global color table
+        0x21, 0xF9, 4, 0, 5, 0, 0, 0, // graphic control, 5 centiseconds
+        0x2C, 0, 0, 0, 0, 1, 0, 1, 0, 0, 2, 2, 0x44, 0x01, 0, // frame
+        0x21, 0xF9, 4, 0, 0, 0, 0, 0, // graphic control, no delay
+        0x2C, 0, 0, 0, 0, 1, 0, 1, 0, 0, 2, 2, 0x44, 0x01, 0, // frame
+        0x2C, 0, 0, 0, 0, 1, 0, 1, 0, 0, 2, 2, 0x44, 0x01, 0,    // frame
+        0x3B, // trailer
+    ];
+
+    #[test]
+    fn gif_frame_delays_reads_every_frame() {
+        assert_eq!(
+            gif_frame_delays(GIF),
+            [
+                Duration::from_millis(50),
+                DEFAULT_GIF_DELAY,
+                DEFAULT_GIF_DELAY
+            ]
+        );
+        // cut off files give what was there without panicking
+        for len in 0..GIF.len() {
+            assert!(gif_frame_delays(&GIF[..len]).len() <= 3);
+        }
+        assert!(gif_frame_delays(b"GIF89a").is_empty());
+    }
+
+    #[test]
+    fn file_type_from_uri_mime_and_bytes() {
+        for (uri, mime, expected) in [
+            ("assets/Logo.PNG", None, Some(".png")),
+            ("C:\\images\\walk.gif", None, Some(".gif")),
+            ("file://assets/icon.qoi", Some("image/png"), Some(".qoi")),
+            (
+                "https://example.com/v1.2/avatar",
+                Some("image/jpeg"),
+                Some(".jpg"),
+            ),
+            ("bytes://icon", Some("image/x-tga"), Some(".tga")),
+            ("bytes://icon", Some("text/plain"), None),
+            ("bytes://icon", None, None),
+        ] {
+            assert_eq!(file_type(uri, mime).as_deref(), expected, "{uri} {mime:?}");
+        }
+
+        assert_eq!(sniff_file_type(GIF), Some(".gif"));
+        assert_eq!(
+            sniff_file_type(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"),
+            Some(".png")
+        );
+        assert_eq!(sniff_file_type(&[0xFF, 0xD8, 0xFF, 0xE0]), Some(".jpg"));
+        assert_eq!(sniff_file_type(b"qoif\0\0\0\x01"), Some(".qoi"));
+        assert_eq!(sniff_file_type(b"<svg"), None);
+        assert_eq!(sniff_file_type(&[]), None);
+    }
+}
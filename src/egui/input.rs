@@ -3,14 +3,151 @@
 use std::time::Instant;
 
 use crate::raylib::*;
-use egui::{Key, Modifiers, PointerButton, Pos2, RawInput, pos2, vec2};
+use egui::{Key, Modifiers, MouseWheelUnit, PointerButton, Pos2, RawInput, Vec2, pos2, vec2};
+
+/// How many points a trackpad delta of 1.0 scrolls, matches egui's native `line_scroll_speed`.
+const POINTS_PER_LINE: f32 = 40.0;
+
+/// One finger on a touch screen, in raylib screen coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TouchPoint {
+    pub id: i32,
+    pub pos: Pos2,
+}
+
+/// Gamepad buttons and sticks that drive egui, see [`Input::set_gamepad`].
+///
+/// Navigation is done by moving the keyboard focus, so the buttons are turned into the
+/// keys egui uses for that.
+#[derive(Clone, Debug)]
+pub struct GamepadMapping {
+    /// Index of the gamepad to read.
+    pub gamepad: i32,
+    /// Move the focus, like the arrow keys.
+    pub up: GamepadButton,
+    pub down: GamepadButton,
+    pub left: GamepadButton,
+    pub right: GamepadButton,
+    /// Move the focus to the next or previous widget, like tab and shift+tab.
+    pub next: GamepadButton,
+    pub previous: GamepadButton,
+    /// Click the focused widget, like enter.
+    pub activate: GamepadButton,
+    /// Drop the focus and close popups, like escape.
+    pub cancel: GamepadButton,
+    /// Stick that moves the focus like the d-pad.
+    pub navigate_stick: Option<(GamepadAxis, GamepadAxis)>,
+    /// How far the navigate stick has to be tilted to move the focus, from 0.0 to 1.0.
+    pub navigate_threshold: f32,
+    /// Stick that scrolls the area under the pointer.
+    pub scroll_stick: Option<(GamepadAxis, GamepadAxis)>,
+    /// Points per second scrolled with the stick fully tilted.
+    pub scroll_speed: f32,
+    /// Scroll stick movement below this is ignored.
+    pub deadzone: f32,
+}
+
+impl Default for GamepadMapping {
+    fn default() -> Self {
+        use GamepadAxis::*;
+        use GamepadButton::*;
+        Self {
+            gamepad: 0,
+            up: GAMEPAD_BUTTON_LEFT_FACE_UP,
+            down: GAMEPAD_BUTTON_LEFT_FACE_DOWN,
+            left: GAMEPAD_BUTTON_LEFT_FACE_LEFT,
+            right: GAMEPAD_BUTTON_LEFT_FACE_RIGHT,
+            next: GAMEPAD_BUTTON_RIGHT_TRIGGER_1,
+            previous: GAMEPAD_BUTTON_LEFT_TRIGGER_1,
+            activate: GAMEPAD_BUTTON_RIGHT_FACE_DOWN,
+            cancel: GAMEPAD_BUTTON_RIGHT_FACE_RIGHT,
+            navigate_stick: Some((GAMEPAD_AXIS_LEFT_X, GAMEPAD_AXIS_LEFT_Y)),
+            navigate_threshold: 0.5,
+            scroll_stick: Some((GAMEPAD_AXIS_RIGHT_X, GAMEPAD_AXIS_RIGHT_Y)),
+            scroll_speed: 1000.0,
+            deadzone: 0.25,
+        }
+    }
+}
+
+/// raylib's input queries with everything egui used this frame filtered out.
+///
+/// Get it from `EguiRaylib::filtered_input` after `run`, game code can then use it in
+/// place of the raylib functions so a click on an egui window doesn't also hit the game
+/// behind it. Releases are passed through so a drag that started in the game can end.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FilteredInput {
+    pub pointer_captured: bool,
+    pub keyboard_captured: bool,
+}
+
+impl FilteredInput {
+    pub fn is_mouse_button_pressed(&self, button: MouseButton) -> bool {
+        !self.pointer_captured && IsMouseButtonPressed(button as i32)
+    }
+
+    pub fn is_mouse_button_down(&self, button: MouseButton) -> bool {
+        !self.pointer_captured && IsMouseButtonDown(button as i32)
+    }
+
+    pub fn is_mouse_button_released(&self, button: MouseButton) -> bool {
+        IsMouseButtonReleased(button as i32)
+    }
+
+    pub fn get_mouse_wheel_move(&self) -> f32 {
+        if self.pointer_captured {
+            0.0
+        } else {
+            GetMouseWheelMove()
+        }
+    }
+
+    pub fn is_key_pressed(&self, key: KeyboardKey) -> bool {
+        !self.keyboard_captured && IsKeyPressed(key)
+    }
+
+    pub fn is_key_pressed_repeat(&self, key: KeyboardKey) -> bool {
+        !self.keyboard_captured && IsKeyPressedRepeat(key as i32)
+    }
+
+    pub fn is_key_down(&self, key: KeyboardKey) -> bool {
+        !self.keyboard_captured && IsKeyDown(key)
+    }
+
+    pub fn is_key_released(&self, key: KeyboardKey) -> bool {
+        IsKeyReleased(key)
+    }
+}
 
 /// Contains and manages everything related to `egui` input.
 pub struct Input {
     dt: Instant,
     pointer_pos: Pos2,
+    pointer_inside: bool,
+    /// Size in pixels of the render texture egui draws into, `None` for the window.
+    target_size: Option<Vec2>,
+    /// Pointer position in texture pixels, injected by the caller.
+    target_pointer: Option<Pos2>,
+    /// Touch points of the last frame, to tell which fingers started, moved or ended.
+    touches: Vec<TouchPoint>,
+    touch_enabled: bool,
+    pinch_distance: Option<f32>,
+    focused: Option<bool>,
+    gamepad: Option<GamepadMapping>,
+    /// Direction the navigation stick was held in last frame, as an arrow key.
+    stick_key: Option<Key>,
+    /// Rect of the widget with keyboard focus as of the last frame, in points.
+    pub(crate) focus_rect: Option<egui::Rect>,
+    /// The pointer was placed on the focused widget to scroll with the stick.
+    stick_pointer: bool,
+    scroll_speed: f32,
+    pasted_image: Option<egui::ColorImage>,
+    scale_override: Option<f32>,
+    pixels_per_point_override: Option<f32>,
     pub(crate) raw: RawInput,
+    /// raylib screen units per egui point.
     pub(crate) scale_factor: f32,
+    pub(crate) zoom_factor: f32,
 }
 
 impl Default for Input {
@@ -18,8 +155,26 @@ impl Default for Input {
         Self {
             dt: Instant::now(),
             pointer_pos: Default::default(),
+            pointer_inside: false,
+            target_size: None,
+            target_pointer: None,
+            touches: Vec::new(),
+            // raylib turns the mouse into a touch point on desktop, egui would then
+            // treat every drag as a touch drag
+            touch_enabled: cfg!(any(target_os = "android", target_arch = "wasm32")),
+            pinch_distance: None,
+            focused: None,
+            gamepad: None,
+            stick_key: None,
+            focus_rect: None,
+            stick_pointer: false,
+            scroll_speed: 1.0,
+            pasted_image: None,
+            scale_override: None,
+            pixels_per_point_override: None,
             raw: Default::default(),
             scale_factor: 1.0,
+            zoom_factor: 1.0,
         }
     }
 }
@@ -35,27 +190,51 @@ impl Input {
 
     /// Updates egui with the latest input from Raylib.
     pub fn update(&mut self) {
-        // Update screen rectangle
-        self.resize_event(GetScreenWidth() as f32, GetScreenHeight() as f32);
+        // Update scale and screen rectangle
+        self.update_scale();
+        let screen_size = match self.target_size {
+            Some(size) => size,
+            None => vec2(GetScreenWidth() as f32, GetScreenHeight() as f32),
+        };
+        self.resize_event(screen_size.x, screen_size.y);
+        if self.target_size.is_none() {
+            self.update_window_info(screen_size);
+        }
 
         let modifiers = translate_modifiers();
+        self.raw.modifiers = modifiers;
 
         // --- Mouse ---
-        let mouse_pos = GetMousePosition();
-        self.pointer_pos = pos2(
-            mouse_pos.x / self.scale_factor,
-            mouse_pos.y / self.scale_factor,
-        );
-        self.raw
-            .events
-            .push(egui::Event::PointerMoved(self.pointer_pos));
+        let mouse_pos = match self.target_size {
+            Some(_) => self.target_pointer,
+            None if IsCursorOnScreen() => {
+                let mouse_pos = GetMousePosition();
+                Some(pos2(mouse_pos.x, mouse_pos.y))
+            }
+            None => None,
+        };
+        match mouse_pos {
+            Some(mouse_pos) => {
+                self.pointer_pos = (mouse_pos.to_vec2() / self.scale_factor).to_pos2();
+                self.pointer_inside = true;
+                self.raw
+                    .events
+                    .push(egui::Event::PointerMoved(self.pointer_pos));
+            }
+            None if self.pointer_inside => {
+                self.pointer_inside = false;
+                self.raw.events.push(egui::Event::PointerGone);
+            }
+            None => {}
+        }
 
         for (rl_button, egui_button) in [
             (MouseButton::MOUSE_BUTTON_LEFT, PointerButton::Primary),
             (MouseButton::MOUSE_BUTTON_RIGHT, PointerButton::Secondary),
             (MouseButton::MOUSE_BUTTON_MIDDLE, PointerButton::Middle),
         ] {
-            if IsMouseButtonPressed(rl_button as i32) {
+            // presses only count over egui, releases always so drags can end outside
+            if self.pointer_inside && IsMouseButtonPressed(rl_button as i32) {
                 self.raw.events.push(egui::Event::PointerButton {
                     pos: self.pointer_pos,
                     button: egui_button,
@@ -75,40 +254,73 @@ impl Input {
 
         // --- Mouse Wheel ---
         let wheel_move = GetMouseWheelMoveV();
-        if wheel_move.x != 0.0 || wheel_move.y != 0.0 {
-            // TODO
-            // self.raw.events.push(egui::Event::Scroll(vec2(
-            //     wheel_move.x * 8.0,
-            //     wheel_move.y * 8.0,
-            // )));
+        if self.pointer_inside && (wheel_move.x != 0.0 || wheel_move.y != 0.0) {
+            self.raw.events.push(translate_wheel(
+                vec2(wheel_move.x, wheel_move.y),
+                self.scroll_speed,
+                modifiers,
+            ));
         }
 
+        // --- Touch ---
+        // like the mouse, touches on a render target come from the caller
+        if self.touch_enabled && self.target_size.is_none() {
+            let touches: Vec<TouchPoint> = (0..GetTouchPointCount())
+                .map(|index| {
+                    let pos = GetTouchPosition(index);
+                    TouchPoint {
+                        id: GetTouchPointId(index),
+                        pos: pos2(pos.x, pos.y),
+                    }
+                })
+                .collect();
+            self.push_touches(&touches);
+            self.update_pinch();
+        }
+
+        // --- Gamepad ---
+        self.update_gamepad();
+
         // --- Keyboard ---
-        // Text input
-        let key_code = GetCharPressed();
-        while key_code != 0 {
-            let ch = char::from_u32(key_code as u32).unwrap_or('\0');
-            if is_printable(ch) {
-                self.raw.events.push(egui::Event::Text(ch.to_string()));
+        // Text input, the char queue is always drained so nothing is left over for the
+        // next frame, even if the text ends up being swallowed by a shortcut.
+        let mut text = String::new();
+        loop {
+            let codepoint = GetCharPressed();
+            if codepoint == 0 {
+                break;
             }
+            if let Some(ch) = char::from_u32(codepoint as u32).filter(|&ch| is_printable(ch)) {
+                text.push(ch);
+            }
+        }
+        if !text.is_empty() && !is_shortcut(modifiers) {
+            self.raw.events.push(egui::Event::Text(text));
         }
 
         // Key presses and releases
         for key in ALL_KEYS {
             if let Some(egui_key) = translate_keycode(key) {
-                if IsKeyPressed(key) {
+                // raylib key codes name the physical key position, we have no separate
+                // layout-aware logical key so both are reported the same.
+                let pressed = IsKeyPressed(key);
+                let repeat = !pressed && IsKeyPressedRepeat(key as i32);
+                if pressed || repeat {
+                    if let Some(event) = self.clipboard_event(egui_key, modifiers) {
+                        self.raw.events.push(event);
+                    }
                     self.raw.events.push(egui::Event::Key {
                         key: egui_key,
-                        physical_key: None,
+                        physical_key: Some(egui_key),
                         pressed: true,
-                        repeat: false,
+                        repeat,
                         modifiers,
                     });
                 }
-                if IsKeyPressed(key) {
+                if IsKeyReleased(key) {
                     self.raw.events.push(egui::Event::Key {
                         key: egui_key,
-                        physical_key: None,
+                        physical_key: Some(egui_key),
                         pressed: false,
                         repeat: false,
                         modifiers,
@@ -118,12 +330,358 @@ impl Input {
         }
     }
 
+    /// Takes the image pasted from the clipboard this frame, if any.
+    ///
+    /// egui has no event for pasted images, so they are kept here for the caller to pick up.
+    pub fn take_pasted_image(&mut self) -> Option<egui::ColorImage> {
+        self.pasted_image.take()
+    }
+
+    /// Read touch points and pinch gestures from raylib, on by default on Android and the web.
+    pub fn set_touch_enabled(&mut self, touch_enabled: bool) {
+        self.touch_enabled = touch_enabled;
+    }
+
+    /// Turn the fingers currently on the screen into egui touch events.
+    ///
+    /// Positions are in raylib screen coordinates, or in texture pixels when egui draws
+    /// into a render texture, like [`Self::set_target_pointer`]. `update` calls this with
+    /// raylib's touch points for the window, it can also be fed synthetic ones:
+    /// ```rust
+    /// use egui::{Event, TouchPhase, pos2};
+    /// use raylib_egui_rs::egui::{Input, TouchPoint};
+    ///
+    /// let mut input = Input::default();
+    /// input.push_touches(&[TouchPoint { id: 7, pos: pos2(10.0, 20.0) }]);
+    /// input.push_touches(&[TouchPoint { id: 7, pos: pos2(15.0, 20.0) }]);
+    /// input.push_touches(&[]);
+    ///
+    /// let phases: Vec<TouchPhase> = input
+    ///     .events()
+    ///     .iter()
+    ///     .filter_map(|event| match event {
+    ///         Event::Touch { phase, .. } => Some(*phase),
+    ///         _ => None,
+    ///     })
+    ///     .collect();
+    /// assert_eq!(phases, [TouchPhase::Start, TouchPhase::Move, TouchPhase::End]);
+    /// ```
+    pub fn push_touches(&mut self, touches: &[TouchPoint]) {
+        let device_id = egui::TouchDeviceId(0);
+        for touch in touches {
+            let phase = match self.touches.iter().find(|old| old.id == touch.id) {
+                None => egui::TouchPhase::Start,
+                Some(old) if old.pos != touch.pos => egui::TouchPhase::Move,
+                Some(_) => continue,
+            };
+            self.raw.events.push(egui::Event::Touch {
+                device_id,
+                id: egui::TouchId(touch.id as u64),
+                phase,
+                pos: (touch.pos.to_vec2() / self.scale_factor).to_pos2(),
+                force: None,
+            });
+        }
+        for old in &self.touches {
+            if !touches.iter().any(|touch| touch.id == old.id) {
+                self.raw.events.push(egui::Event::Touch {
+                    device_id,
+                    id: egui::TouchId(old.id as u64),
+                    phase: egui::TouchPhase::End,
+                    pos: (old.pos.to_vec2() / self.scale_factor).to_pos2(),
+                    force: None,
+                });
+            }
+        }
+        self.touches.clear();
+        self.touches.extend_from_slice(touches);
+    }
+
+    /// The events gathered for the next egui frame.
+    pub fn events(&self) -> &[egui::Event] {
+        &self.raw.events
+    }
+
+    /// Turn raylib's pinch gesture into egui zoom, relative to the distance of the fingers
+    /// in the last frame.
+    fn update_pinch(&mut self) {
+        let pinching = IsGestureDetected(Gesture::GESTURE_PINCH_IN as u32)
+            || IsGestureDetected(Gesture::GESTURE_PINCH_OUT as u32);
+        if !pinching {
+            self.pinch_distance = None;
+            return;
+        }
+        let pinch = GetGesturePinchVector();
+        let distance = vec2(pinch.x, pinch.y).length();
+        if let Some(last) = self.pinch_distance
+            && last > 0.0
+            && distance > 0.0
+        {
+            self.raw.events.push(egui::Event::Zoom(distance / last));
+        }
+        self.pinch_distance = Some(distance);
+    }
+
+    /// Drive egui with a gamepad, `None` turns it off again.
+    pub fn set_gamepad(&mut self, mapping: Option<GamepadMapping>) {
+        self.gamepad = mapping;
+        self.release_sticks();
+    }
+
+    pub fn gamepad(&self) -> Option<&GamepadMapping> {
+        self.gamepad.as_ref()
+    }
+
+    /// Turns gamepad buttons into navigation keys and the scroll stick into wheel events.
+    fn update_gamepad(&mut self) {
+        let Some(mapping) = &self.gamepad else {
+            return;
+        };
+        let gamepad = mapping.gamepad;
+        if !IsGamepadAvailable(gamepad) {
+            // a pad unplugged mid-tilt would leave the arrow key held down
+            self.release_sticks();
+            return;
+        }
+
+        let shift = Modifiers {
+            shift: true,
+            ..Default::default()
+        };
+        let mut events = Vec::new();
+        for (button, key, modifiers) in [
+            (mapping.up, Key::ArrowUp, Modifiers::NONE),
+            (mapping.down, Key::ArrowDown, Modifiers::NONE),
+            (mapping.left, Key::ArrowLeft, Modifiers::NONE),
+            (mapping.right, Key::ArrowRight, Modifiers::NONE),
+            (mapping.next, Key::Tab, Modifiers::NONE),
+            (mapping.previous, Key::Tab, shift),
+            (mapping.activate, Key::Enter, Modifiers::NONE),
+            (mapping.cancel, Key::Escape, Modifiers::NONE),
+        ] {
+            if IsGamepadButtonPressed(gamepad, button as i32) {
+                events.push(key_event(key, true, modifiers));
+            }
+            if IsGamepadButtonReleased(gamepad, button as i32) {
+                events.push(key_event(key, false, modifiers));
+            }
+        }
+
+        // the stick presses an arrow key once it is tilted past the threshold and releases
+        // it again when it comes back or turns to another direction
+        let stick_key = mapping.navigate_stick.and_then(|(x_axis, y_axis)| {
+            let stick = vec2(
+                GetGamepadAxisMovement(gamepad, x_axis as i32),
+                GetGamepadAxisMovement(gamepad, y_axis as i32),
+            );
+            if stick.length() < mapping.navigate_threshold {
+                None
+            } else if stick.x.abs() > stick.y.abs() {
+                Some(if stick.x > 0.0 {
+                    Key::ArrowRight
+                } else {
+                    Key::ArrowLeft
+                })
+            } else {
+                Some(if stick.y > 0.0 {
+                    Key::ArrowDown
+                } else {
+                    Key::ArrowUp
+                })
+            }
+        });
+        if stick_key != self.stick_key {
+            if let Some(key) = self.stick_key {
+                events.push(key_event(key, false, Modifiers::NONE));
+            }
+            if let Some(key) = stick_key {
+                events.push(key_event(key, true, Modifiers::NONE));
+            }
+        }
+
+        if let Some((x_axis, y_axis)) = mapping.scroll_stick {
+            let stick = vec2(
+                GetGamepadAxisMovement(gamepad, x_axis as i32),
+                GetGamepadAxisMovement(gamepad, y_axis as i32),
+            );
+            let delta = stick * mapping.scroll_speed * GetFrameTime();
+            let deadzone = mapping.deadzone;
+            events.extend(self.stick_scroll_events(stick.length() > deadzone, delta));
+        }
+
+        self.stick_key = stick_key;
+        self.raw.events.extend(events);
+    }
+
+    /// Turns the platform clipboard shortcuts into egui clipboard events.
+    /// Let go of what the sticks hold, the navigation key and the scroll pointer.
+    fn release_sticks(&mut self) {
+        if let Some(key) = self.stick_key.take() {
+            self.raw.events.push(key_event(key, false, Modifiers::NONE));
+        }
+        if std::mem::take(&mut self.stick_pointer) {
+            self.raw.events.push(egui::Event::PointerGone);
+        }
+    }
+
+    /// Scroll what the stick points at, `delta` in points with down and right positive.
+    ///
+    /// egui scrolls the area under the pointer, without a mouse the pointer is put where
+    /// the focused widget is when the stick gets tilted, and taken away on release.
+    fn stick_scroll_events(&mut self, tilted: bool, delta: Vec2) -> Vec<egui::Event> {
+        let mut events = Vec::new();
+        if self.pointer_inside {
+            self.stick_pointer = false;
+        } else if tilted {
+            // placed once, the widget may scroll away but the area under it stays
+            if !self.stick_pointer
+                && let Some(focus_rect) = self.focus_rect
+            {
+                events.push(egui::Event::PointerMoved(focus_rect.center()));
+                self.stick_pointer = true;
+            }
+        } else if self.stick_pointer {
+            events.push(egui::Event::PointerGone);
+            self.stick_pointer = false;
+        }
+        if tilted {
+            // tilting the stick up scrolls up, like turning the wheel away
+            events.push(egui::Event::MouseWheel {
+                unit: MouseWheelUnit::Point,
+                delta: -delta,
+                modifiers: Modifiers::NONE,
+            });
+        }
+        events
+    }
+
+    fn clipboard_event(&mut self, key: Key, modifiers: Modifiers) -> Option<egui::Event> {
+        let is_cut = (modifiers.command && key == Key::X)
+            || (cfg!(target_os = "windows") && modifiers.shift && key == Key::Delete);
+        let is_copy = (modifiers.command && key == Key::C)
+            || (cfg!(target_os = "windows") && modifiers.ctrl && key == Key::Insert);
+        let is_paste = (modifiers.command && key == Key::V)
+            || (cfg!(target_os = "windows") && modifiers.shift && key == Key::Insert);
+
+        if is_cut {
+            Some(egui::Event::Cut)
+        } else if is_copy {
+            Some(egui::Event::Copy)
+        } else if is_paste {
+            self.paste_event()
+        } else {
+            None
+        }
+    }
+
+    /// Read the clipboard for a paste, `None` if it holds no text.
+    ///
+    /// An image on the clipboard is kept for [`Self::take_pasted_image`] instead.
+    pub(crate) fn paste_event(&mut self) -> Option<egui::Event> {
+        let text = GetClipboardText();
+        if text.is_empty() {
+            self.pasted_image = clipboard_image();
+            None
+        } else {
+            Some(egui::Event::Paste(text.replace("\r\n", "\n")))
+        }
+    }
+
     /// Set the scale_factor and update the screen_rect
+    ///
+    /// This overrides the scale factor that otherwise follows the DPI of the monitor.
     pub fn set_scale_factor(&mut self, scale_factor: f32, (w, h): (f32, f32)) {
-        self.scale_factor = scale_factor;
+        self.scale_override = Some(scale_factor);
+        self.scale_factor = scale_factor * self.zoom_factor;
         self.resize_event(w, h);
     }
 
+    /// Size egui to a render texture instead of the window, `None` goes back to the window.
+    ///
+    /// The pointer then no longer follows the mouse, it has to be set with
+    /// [`Self::set_target_pointer`].
+    pub fn set_target_size(&mut self, size: Option<Vec2>) {
+        self.target_size = size;
+    }
+
+    /// Set the pointer position in texture pixels, `None` when it is not over the texture.
+    ///
+    /// For a texture on a 3D quad this comes from where `GetRayCollisionQuad` hits it.
+    pub fn set_target_pointer(&mut self, pos: Option<Pos2>) {
+        self.target_pointer = pos;
+    }
+
+    /// Use a fixed number of framebuffer pixels per point instead of the monitor DPI.
+    pub fn set_pixels_per_point(&mut self, pixels_per_point: Option<f32>) {
+        self.pixels_per_point_override = pixels_per_point;
+    }
+
+    /// Go back to deriving the scale factor from the monitor DPI.
+    pub fn clear_scale_factor(&mut self) {
+        self.scale_override = None;
+    }
+
+    /// Tell egui the pixel density of the monitor and derive the scale factor from it.
+    ///
+    /// With `FLAG_WINDOW_HIGHDPI` raylib already scales screen coordinates to the
+    /// framebuffer, that part is taken out of the scale factor again.
+    fn update_scale(&mut self) {
+        // a render texture has no monitor, one point is one texture pixel by default
+        let (render_scale, monitor_pixels_per_point) = match self.target_size {
+            Some(_) => (1.0, 1.0),
+            None => (render_scale(), GetWindowScaleDPI().x),
+        };
+        let native_pixels_per_point = match (self.scale_override, self.pixels_per_point_override) {
+            (Some(scale_factor), _) => scale_factor * render_scale,
+            (None, Some(pixels_per_point)) => pixels_per_point,
+            (None, None) => monitor_pixels_per_point,
+        };
+        self.raw
+            .viewports
+            .entry(egui::ViewportId::ROOT)
+            .or_default()
+            .native_pixels_per_point = Some(native_pixels_per_point);
+        self.scale_factor = native_pixels_per_point * self.zoom_factor / render_scale;
+    }
+
+    /// Report the window state to egui, and focus changes as events.
+    fn update_window_info(&mut self, screen_size: Vec2) {
+        let focused = IsWindowFocused();
+        if self.focused != Some(focused) {
+            self.focused = Some(focused);
+            self.raw.events.push(egui::Event::WindowFocused(focused));
+        }
+        self.raw.focused = focused;
+
+        let position = GetWindowPosition();
+        let inner_rect = egui::Rect::from_min_size(
+            pos2(position.x, position.y) / self.scale_factor,
+            screen_size / self.scale_factor,
+        );
+        let monitor = GetCurrentMonitor();
+        let monitor_size = vec2(
+            GetMonitorWidth(monitor) as f32,
+            GetMonitorHeight(monitor) as f32,
+        ) / self.scale_factor;
+
+        let info = self
+            .raw
+            .viewports
+            .entry(egui::ViewportId::ROOT)
+            .or_default();
+        info.inner_rect = Some(inner_rect);
+        info.monitor_size = Some(monitor_size);
+        info.focused = Some(focused);
+        info.fullscreen = Some(IsWindowFullscreen());
+        info.maximized = Some(IsWindowMaximized());
+        info.minimized = Some(IsWindowMinimized());
+    }
+
+    /// Set the multiplier applied to mouse wheel and trackpad scrolling, defaults to 1.0
+    pub fn set_scroll_speed(&mut self, scroll_speed: f32) {
+        self.scroll_speed = scroll_speed;
+    }
+
     /// Update screen_rect data with window size
     pub fn resize_event(&mut self, w: f32, h: f32) {
         self.raw.screen_rect = Some(egui::Rect::from_min_size(
@@ -142,6 +700,60 @@ fn is_printable(chr: char) -> bool {
     !is_in_private_use_area && !chr.is_ascii_control()
 }
 
+/// Framebuffer pixels per raylib screen unit, above 1.0 with `FLAG_WINDOW_HIGHDPI`.
+pub(crate) fn render_scale() -> f32 {
+    let screen_width = GetScreenWidth();
+    if screen_width > 0 {
+        GetRenderWidth() as f32 / screen_width as f32
+    } else {
+        1.0
+    }
+}
+
+fn key_event(key: Key, pressed: bool, modifiers: Modifiers) -> egui::Event {
+    egui::Event::Key {
+        key,
+        physical_key: Some(key),
+        pressed,
+        repeat: false,
+        modifiers,
+    }
+}
+
+/// Picks the egui unit for a raylib wheel delta. Mouse wheels report whole notches which
+/// egui scrolls line by line, trackpads report fractions which are scrolled smoothly in points.
+fn translate_wheel(delta: Vec2, scroll_speed: f32, mut modifiers: Modifiers) -> egui::Event {
+    let (unit, delta) = if delta.x.fract() == 0.0 && delta.y.fract() == 0.0 {
+        (MouseWheelUnit::Line, delta)
+    } else {
+        (MouseWheelUnit::Point, delta * POINTS_PER_LINE)
+    };
+    // egui zooms when the wheel is turned with `command` held, on macOS that is
+    // cmd only, so ctrl is folded in to make ctrl+wheel zoom everywhere.
+    modifiers.command |= modifiers.ctrl;
+    egui::Event::MouseWheel {
+        unit,
+        delta: delta * scroll_speed,
+        modifiers,
+    }
+}
+
+#[cfg(feature = "SUPPORT_CLIPBOARD_IMAGE")]
+fn clipboard_image() -> Option<egui::ColorImage> {
+    super::image_loader::color_image_from_raylib(GetClipboardImage()).ok()
+}
+
+#[cfg(not(feature = "SUPPORT_CLIPBOARD_IMAGE"))]
+fn clipboard_image() -> Option<egui::ColorImage> {
+    None
+}
+
+/// Whether the modifiers turn typing into a shortcut, like cmd+c on macOS which still
+/// produces the char 'c'. AltGr shows up as ctrl+alt on Windows and keeps producing text.
+fn is_shortcut(modifiers: Modifiers) -> bool {
+    (modifiers.command || modifiers.mac_cmd) && !(modifiers.ctrl && modifiers.alt)
+}
+
 fn translate_modifiers() -> Modifiers {
     Modifiers {
         alt: IsKeyDown(KeyboardKey::KEY_LEFT_ALT) || IsKeyDown(KeyboardKey::KEY_RIGHT_ALT),
@@ -174,6 +786,27 @@ fn translate_keycode(key: KeyboardKey) -> Option<egui::Key> {
         KeyboardKey::KEY_ENTER => Key::Enter,
         KeyboardKey::KEY_TAB => Key::Tab,
         KeyboardKey::KEY_SPACE => Key::Space,
+        KeyboardKey::KEY_APOSTROPHE => Key::Quote,
+        KeyboardKey::KEY_COMMA => Key::Comma,
+        KeyboardKey::KEY_MINUS => Key::Minus,
+        KeyboardKey::KEY_PERIOD => Key::Period,
+        KeyboardKey::KEY_SLASH => Key::Slash,
+        KeyboardKey::KEY_SEMICOLON => Key::Semicolon,
+        KeyboardKey::KEY_EQUAL => Key::Equals,
+        KeyboardKey::KEY_LEFT_BRACKET => Key::OpenBracket,
+        KeyboardKey::KEY_BACKSLASH => Key::Backslash,
+        KeyboardKey::KEY_RIGHT_BRACKET => Key::CloseBracket,
+        KeyboardKey::KEY_GRAVE => Key::Backtick,
+        KeyboardKey::KEY_ZERO => Key::Num0,
+        KeyboardKey::KEY_ONE => Key::Num1,
+        KeyboardKey::KEY_TWO => Key::Num2,
+        KeyboardKey::KEY_THREE => Key::Num3,
+        KeyboardKey::KEY_FOUR => Key::Num4,
+        KeyboardKey::KEY_FIVE => Key::Num5,
+        KeyboardKey::KEY_SIX => Key::Num6,
+        KeyboardKey::KEY_SEVEN => Key::Num7,
+        KeyboardKey::KEY_EIGHT => Key::Num8,
+        KeyboardKey::KEY_NINE => Key::Num9,
         KeyboardKey::KEY_A => Key::A,
         KeyboardKey::KEY_B => Key::B,
         KeyboardKey::KEY_C => Key::C,
@@ -200,13 +833,42 @@ fn translate_keycode(key: KeyboardKey) -> Option<egui::Key> {
         KeyboardKey::KEY_X => Key::X,
         KeyboardKey::KEY_Y => Key::Y,
         KeyboardKey::KEY_Z => Key::Z,
+        KeyboardKey::KEY_F1 => Key::F1,
+        KeyboardKey::KEY_F2 => Key::F2,
+        KeyboardKey::KEY_F3 => Key::F3,
+        KeyboardKey::KEY_F4 => Key::F4,
+        KeyboardKey::KEY_F5 => Key::F5,
+        KeyboardKey::KEY_F6 => Key::F6,
+        KeyboardKey::KEY_F7 => Key::F7,
+        KeyboardKey::KEY_F8 => Key::F8,
+        KeyboardKey::KEY_F9 => Key::F9,
+        KeyboardKey::KEY_F10 => Key::F10,
+        KeyboardKey::KEY_F11 => Key::F11,
+        KeyboardKey::KEY_F12 => Key::F12,
+        KeyboardKey::KEY_KP_0 => Key::Num0,
+        KeyboardKey::KEY_KP_1 => Key::Num1,
+        KeyboardKey::KEY_KP_2 => Key::Num2,
+        KeyboardKey::KEY_KP_3 => Key::Num3,
+        KeyboardKey::KEY_KP_4 => Key::Num4,
+        KeyboardKey::KEY_KP_5 => Key::Num5,
+        KeyboardKey::KEY_KP_6 => Key::Num6,
+        KeyboardKey::KEY_KP_7 => Key::Num7,
+        KeyboardKey::KEY_KP_8 => Key::Num8,
+        KeyboardKey::KEY_KP_9 => Key::Num9,
+        KeyboardKey::KEY_KP_DECIMAL => Key::Period,
+        KeyboardKey::KEY_KP_DIVIDE => Key::Slash,
+        KeyboardKey::KEY_KP_SUBTRACT => Key::Minus,
+        KeyboardKey::KEY_KP_ADD => Key::Plus,
+        KeyboardKey::KEY_KP_ENTER => Key::Enter,
+        KeyboardKey::KEY_KP_EQUAL => Key::Equals,
+        KeyboardKey::KEY_BACK => Key::BrowserBack,
         _ => return None,
     })
 }
 
 // A list of all keys we care about. Raylib doesn't have a way to iterate
 // over pressed keys, so we check them all.
-const ALL_KEYS: [KeyboardKey; 41] = [
+const ALL_KEYS: [KeyboardKey; 91] = [
     KeyboardKey::KEY_ESCAPE,
     KeyboardKey::KEY_INSERT,
     KeyboardKey::KEY_HOME,
@@ -222,6 +884,27 @@ const ALL_KEYS: [KeyboardKey; 41] = [
     KeyboardKey::KEY_ENTER,
     KeyboardKey::KEY_TAB,
     KeyboardKey::KEY_SPACE,
+    KeyboardKey::KEY_APOSTROPHE,
+    KeyboardKey::KEY_COMMA,
+    KeyboardKey::KEY_MINUS,
+    KeyboardKey::KEY_PERIOD,
+    KeyboardKey::KEY_SLASH,
+    KeyboardKey::KEY_SEMICOLON,
+    KeyboardKey::KEY_EQUAL,
+    KeyboardKey::KEY_LEFT_BRACKET,
+    KeyboardKey::KEY_BACKSLASH,
+    KeyboardKey::KEY_RIGHT_BRACKET,
+    KeyboardKey::KEY_GRAVE,
+    KeyboardKey::KEY_ZERO,
+    KeyboardKey::KEY_ONE,
+    KeyboardKey::KEY_TWO,
+    KeyboardKey::KEY_THREE,
+    KeyboardKey::KEY_FOUR,
+    KeyboardKey::KEY_FIVE,
+    KeyboardKey::KEY_SIX,
+    KeyboardKey::KEY_SEVEN,
+    KeyboardKey::KEY_EIGHT,
+    KeyboardKey::KEY_NINE,
     KeyboardKey::KEY_A,
     KeyboardKey::KEY_B,
     KeyboardKey::KEY_C,
@@ -248,4 +931,151 @@ const ALL_KEYS: [KeyboardKey; 41] = [
     KeyboardKey::KEY_X,
     KeyboardKey::KEY_Y,
     KeyboardKey::KEY_Z,
+    KeyboardKey::KEY_F1,
+    KeyboardKey::KEY_F2,
+    KeyboardKey::KEY_F3,
+    KeyboardKey::KEY_F4,
+    KeyboardKey::KEY_F5,
+    KeyboardKey::KEY_F6,
+    KeyboardKey::KEY_F7,
+    KeyboardKey::KEY_F8,
+    KeyboardKey::KEY_F9,
+    KeyboardKey::KEY_F10,
+    KeyboardKey::KEY_F11,
+    KeyboardKey::KEY_F12,
+    KeyboardKey::KEY_KP_0,
+    KeyboardKey::KEY_KP_1,
+    KeyboardKey::KEY_KP_2,
+    KeyboardKey::KEY_KP_3,
+    KeyboardKey::KEY_KP_4,
+    KeyboardKey::KEY_KP_5,
+    KeyboardKey::KEY_KP_6,
+    KeyboardKey::KEY_KP_7,
+    KeyboardKey::KEY_KP_8,
+    KeyboardKey::KEY_KP_9,
+    KeyboardKey::KEY_KP_DECIMAL,
+    KeyboardKey::KEY_KP_DIVIDE,
+    KeyboardKey::KEY_KP_SUBTRACT,
+    KeyboardKey::KEY_KP_ADD,
+    KeyboardKey::KEY_KP_ENTER,
+    KeyboardKey::KEY_KP_EQUAL,
+    KeyboardKey::KEY_BACK,
 ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run a frame of a scroll area with the first button focused, returns its offset.
+    fn scroll_frame(ctx: &egui::Context, input: &mut Input) -> f32 {
+        let mut raw = input.take();
+        raw.predicted_dt = 1.0 / 60.0;
+        raw.screen_rect = Some(egui::Rect::from_min_size(Pos2::ZERO, vec2(200.0, 200.0)));
+        let mut offset = 0.0;
+        let _ = ctx.run(raw, |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                let output = egui::ScrollArea::vertical().show(ui, |ui| {
+                    for i in 0..100 {
+                        let response = ui.button(format!("item {i}"));
+                        if i == 0 && ui.memory(|mem| mem.focused().is_none()) {
+                            response.request_focus();
+                        }
+                    }
+                });
+                offset = output.state.offset.y;
+            });
+        });
+        offset
+    }
+
+    #[test]
+    fn stick_scrolls_focused_area_without_mouse() {
+        let ctx = egui::Context::default();
+        let mut input = Input::default();
+        scroll_frame(&ctx, &mut input);
+        scroll_frame(&ctx, &mut input);
+        input.focus_rect = ctx
+            .memory(|mem| mem.focused())
+            .and_then(|id| ctx.read_response(id))
+            .map(|response| response.rect);
+        assert!(input.focus_rect.is_some());
+        assert!(!input.pointer_inside);
+
+        let mut offset = 0.0;
+        for _ in 0..30 {
+            let events = input.stick_scroll_events(true, vec2(0.0, 20.0));
+            input.raw.events.extend(events);
+            offset = scroll_frame(&ctx, &mut input);
+        }
+        assert!(offset > 0.0, "the stick did not scroll");
+
+        let events = input.stick_scroll_events(false, Vec2::ZERO);
+        assert!(matches!(events[..], [egui::Event::PointerGone]));
+        assert!(input.stick_scroll_events(false, Vec2::ZERO).is_empty());
+    }
+
+    #[test]
+    fn translate_wheel_units_and_modifiers() {
+        let wheel = |unit, delta, modifiers| egui::Event::MouseWheel {
+            unit,
+            delta,
+            modifiers,
+        };
+        let ctrl_command = Modifiers {
+            ctrl: true,
+            command: true,
+            ..Modifiers::NONE
+        };
+        for (delta, scroll_speed, modifiers, expected) in [
+            // whole notches are lines
+            (
+                vec2(0.0, 1.0),
+                1.0,
+                Modifiers::NONE,
+                wheel(MouseWheelUnit::Line, vec2(0.0, 1.0), Modifiers::NONE),
+            ),
+            (
+                vec2(-2.0, 0.0),
+                2.0,
+                Modifiers::NONE,
+                wheel(MouseWheelUnit::Line, vec2(-4.0, 0.0), Modifiers::NONE),
+            ),
+            // fractions are trackpad points
+            (
+                vec2(0.0, 0.5),
+                1.0,
+                Modifiers::NONE,
+                wheel(
+                    MouseWheelUnit::Point,
+                    vec2(0.0, 0.5 * POINTS_PER_LINE),
+                    Modifiers::NONE,
+                ),
+            ),
+            (
+                vec2(0.25, 1.0),
+                2.0,
+                Modifiers::SHIFT,
+                wheel(
+                    MouseWheelUnit::Point,
+                    vec2(0.5 * POINTS_PER_LINE, 2.0 * POINTS_PER_LINE),
+                    Modifiers::SHIFT,
+                ),
+            ),
+            // ctrl zooms like command
+            (
+                vec2(0.0, 1.0),
+                1.0,
+                Modifiers::CTRL,
+                wheel(MouseWheelUnit::Line, vec2(0.0, 1.0), ctrl_command),
+            ),
+            (
+                vec2(0.0, 1.0),
+                1.0,
+                Modifiers::COMMAND,
+                wheel(MouseWheelUnit::Line, vec2(0.0, 1.0), Modifiers::COMMAND),
+            ),
+        ] {
+            assert_eq!(translate_wheel(delta, scroll_speed, modifiers), expected);
+        }
+    }
+}
@@ -1,41 +1,389 @@
+mod buffers;
+mod builder;
+mod fonts;
+mod image_loader;
 mod input;
 mod painter;
-pub use input::Input;
-pub use painter::Painter;
+mod shader;
+#[cfg(feature = "persistence")]
+mod storage;
+pub use builder::EguiRaylibBuilder;
+pub use fonts::FontsBuilder;
+pub use image_loader::RaylibImageLoader;
+pub use input::{FilteredInput, GamepadMapping, Input, TouchPoint};
+pub use painter::{CallbackFn, Painter, begin_mode_3d};
+#[cfg(feature = "persistence")]
+pub use storage::{FileStorage, MEMORY_KEY, MemoryStorage, Storage, load_memory, save_memory};
+
+use crate::color::Color;
+use crate::raylib;
+use crate::raylib::ConfigFlags;
+use egui::{CursorIcon, ViewportCommand};
+use std::borrow::Cow;
+use std::time::Duration;
 
-#[derive(Default)]
 pub struct EguiRaylib {
     pub painter: Painter,
     pub input: Input,
     shapes: Vec<egui::epaint::ClippedShape>,
     textures_delta: egui::TexturesDelta,
+    platform_output: egui::PlatformOutput,
+    cursor_icon: Option<CursorIcon>,
+    close_requested: bool,
+    repaint_delay: Duration,
+    render_target: Option<raylib::RenderTexture2D>,
+    #[cfg(feature = "persistence")]
+    storage: Option<Box<dyn Storage>>,
+    #[cfg(feature = "persistence")]
+    autosave_interval: Duration,
+    #[cfg(feature = "persistence")]
+    last_save: f64,
     ctx: egui::Context,
 }
 
 impl EguiRaylib {
+    /// Create with the default settings, see [`Self::builder`] for more.
     pub fn new() -> Self {
+        Self::builder().build()
+    }
+
+    pub fn builder() -> EguiRaylibBuilder {
+        EguiRaylibBuilder::new()
+    }
+
+    /// Everything at its zero state, the builder applies the settings on top.
+    fn empty() -> Self {
         Self {
-            painter: Painter::new(),
-            shapes: Default::default(),
-            textures_delta: Default::default(),
+            painter: Painter::default(),
             input: Input::default(),
+            shapes: Vec::new(),
+            textures_delta: egui::TexturesDelta::default(),
+            platform_output: egui::PlatformOutput::default(),
+            cursor_icon: None,
+            close_requested: false,
+            repaint_delay: Duration::ZERO,
+            render_target: None,
+            #[cfg(feature = "persistence")]
+            storage: None,
+            #[cfg(feature = "persistence")]
+            autosave_interval: Duration::ZERO,
+            #[cfg(feature = "persistence")]
+            last_save: 0.0,
             ctx: egui::Context::default(),
         }
     }
 
-    pub fn run(&mut self, run_ui: impl FnMut(&egui::Context)) {
+    /// The egui context, for app-level configuration like style, fonts or memory.
+    pub fn ctx(&self) -> &egui::Context {
+        &self.ctx
+    }
+
+    pub fn run(&mut self, mut run_ui: impl FnMut(&egui::Context)) {
+        self.input.zoom_factor = self.ctx.zoom_factor();
+        self.input.focus_rect = focused_rect(&self.ctx);
         self.input.update();
+        let focus_ring = self.input.gamepad().is_some();
         let egui::FullOutput {
+            platform_output,
             textures_delta,
             shapes,
+            mut viewport_output,
             ..
-        } = self.ctx.run(self.input.take(), run_ui);
+        } = self.ctx.run(self.input.take(), |ctx| {
+            run_ui(ctx);
+            if focus_ring {
+                paint_focus_ring(ctx);
+            }
+        });
 
+        if let Some(viewport_output) = viewport_output.remove(&egui::ViewportId::ROOT) {
+            self.repaint_delay = viewport_output.repaint_delay;
+            self.handle_viewport_commands(viewport_output.commands);
+        }
+        self.handle_platform_output(&platform_output);
+        self.platform_output = platform_output;
         self.shapes = shapes;
         self.textures_delta.append(textures_delta);
+
+        #[cfg(feature = "persistence")]
+        if self.storage.is_some() {
+            let now = raylib::GetTime();
+            if now - self.last_save >= self.autosave_interval.as_secs_f64() {
+                self.last_save = now;
+                self.save();
+            }
+        }
+    }
+
+    /// Save egui's memory to the storage set with `EguiRaylibBuilder::storage` right away.
+    ///
+    /// This also happens every autosave interval and when `EguiRaylib` is dropped. Needs no
+    /// window, so it also works after `CloseWindow`.
+    #[cfg(feature = "persistence")]
+    pub fn save(&mut self) {
+        if let Some(storage) = self.storage.as_deref_mut()
+            && let Err(err) = save_memory(&self.ctx, storage)
+        {
+            storage::log_warning(&err);
+        }
+    }
+
+    /// Check if the application should close, either because raylib says so or because
+    /// egui sent a `ViewportCommand::Close`.
+    pub fn should_close(&self) -> bool {
+        self.close_requested || raylib::WindowShouldClose()
+    }
+
+    /// True if egui is using the pointer, like dragging a window or hovering a widget that
+    /// waits for a click, as of the last `run`.
+    pub fn wants_pointer_input(&self) -> bool {
+        self.ctx.wants_pointer_input()
+    }
+
+    /// True if egui wants the keyboard, like when a text field has focus.
+    pub fn wants_keyboard_input(&self) -> bool {
+        self.ctx.wants_keyboard_input()
+    }
+
+    /// True if the pointer is over any egui area, including empty panel space.
+    pub fn is_pointer_over_area(&self) -> bool {
+        self.ctx.is_pointer_over_area()
+    }
+
+    /// raylib input with what egui took this frame filtered out, call after `run`.
+    pub fn filtered_input(&self) -> FilteredInput {
+        FilteredInput {
+            pointer_captured: self.wants_pointer_input() || self.is_pointer_over_area(),
+            keyboard_captured: self.wants_keyboard_input(),
+        }
+    }
+
+    /// How long until egui wants to be run again, as of the last `run`.
+    ///
+    /// Zero while something is animating, `Duration::MAX` if only input can change the ui.
+    pub fn repaint_delay(&self) -> Duration {
+        self.repaint_delay
+    }
+
+    /// End the frame like `EndDrawing`, then sleep until there is input or egui asks for
+    /// the next repaint.
+    ///
+    /// Use it in place of `EndDrawing` for tools that should not burn CPU while idle.
+    /// While egui animates the loop runs continuously. When only input can change the ui
+    /// raylib waits for input events. When egui wants a repaint later, like for a blinking
+    /// text cursor, it sleeps until then, but at most `timeout`: raylib can't wait for
+    /// input and a deadline at the same time, input that comes in during the sleep waits
+    /// for it to end.
+    ///
+    /// Neither the sleep nor raylib's event waiting can be woken from another thread, a
+    /// `request_repaint` from one shows once there is input or the sleep ends.
+    pub fn end_drawing_reactive(&self, timeout: Duration) {
+        if self.repaint_delay == Duration::MAX {
+            raylib::EnableEventWaiting();
+            raylib::EndDrawing();
+        } else {
+            raylib::DisableEventWaiting();
+            raylib::EndDrawing();
+            let sleep = self.repaint_delay.min(timeout);
+            if !sleep.is_zero() {
+                raylib::WaitTime(sleep.as_secs_f64());
+            }
+        }
+    }
+
+    /// The platform output of the last `run`.
+    ///
+    /// Clipboard, cursor and url requests are already applied, the rest, like the IME
+    /// cursor position or accessibility events, is left for the caller.
+    pub fn platform_output(&self) -> &egui::PlatformOutput {
+        &self.platform_output
+    }
+
+    fn handle_platform_output(&mut self, platform_output: &egui::PlatformOutput) {
+        for command in &platform_output.commands {
+            match command {
+                egui::OutputCommand::CopyText(text) => raylib::SetClipboardText(&without_nul(text)),
+                // raylib can only read images from the clipboard
+                egui::OutputCommand::CopyImage(_) => {}
+                // a NUL can't be part of a valid url, don't open a truncated one
+                egui::OutputCommand::OpenUrl(open_url) => {
+                    if !open_url.url.contains('\0') {
+                        raylib::OpenURL(&open_url.url)
+                    }
+                }
+            }
+        }
+        // the cursor belongs to the window, not to an offscreen ui
+        if self.render_target.is_none() {
+            self.set_cursor_icon(platform_output.cursor_icon);
+        }
+    }
+
+    fn handle_viewport_commands(&mut self, commands: Vec<ViewportCommand>) {
+        // egui talks in points, raylib windows in screen coordinates
+        let scale = self.input.scale_factor;
+        for command in commands {
+            match command {
+                ViewportCommand::Close => self.close_requested = true,
+                ViewportCommand::CancelClose => self.close_requested = false,
+                ViewportCommand::Title(title) => raylib::SetWindowTitle(&without_nul(&title)),
+                ViewportCommand::Visible(visible) => {
+                    set_window_flag(ConfigFlags::FLAG_WINDOW_HIDDEN, !visible)
+                }
+                ViewportCommand::OuterPosition(pos) => {
+                    raylib::SetWindowPosition((pos.x * scale) as i32, (pos.y * scale) as i32)
+                }
+                ViewportCommand::InnerSize(size) => {
+                    raylib::SetWindowSize((size.x * scale) as i32, (size.y * scale) as i32)
+                }
+                ViewportCommand::MinInnerSize(size) => {
+                    raylib::SetWindowMinSize((size.x * scale) as i32, (size.y * scale) as i32)
+                }
+                ViewportCommand::MaxInnerSize(size) => {
+                    raylib::SetWindowMaxSize((size.x * scale) as i32, (size.y * scale) as i32)
+                }
+                ViewportCommand::Resizable(resizable) => {
+                    set_window_flag(ConfigFlags::FLAG_WINDOW_RESIZABLE, resizable)
+                }
+                ViewportCommand::Minimized(true) => raylib::MinimizeWindow(),
+                ViewportCommand::Maximized(true) => raylib::MaximizeWindow(),
+                ViewportCommand::Minimized(false) | ViewportCommand::Maximized(false) => {
+                    raylib::RestoreWindow()
+                }
+                ViewportCommand::Fullscreen(fullscreen)
+                    if fullscreen != raylib::IsWindowFullscreen() =>
+                {
+                    raylib::ToggleFullscreen()
+                }
+                ViewportCommand::Decorations(decorations) => {
+                    set_window_flag(ConfigFlags::FLAG_WINDOW_UNDECORATED, !decorations)
+                }
+                ViewportCommand::WindowLevel(level) => set_window_flag(
+                    ConfigFlags::FLAG_WINDOW_TOPMOST,
+                    level == egui::WindowLevel::AlwaysOnTop,
+                ),
+                ViewportCommand::Icon(Some(icon)) => {
+                    let mut rgba = icon.rgba.clone();
+                    raylib::SetWindowIcon(raylib::Image {
+                        data: rgba.as_mut_ptr() as *mut std::ffi::c_void,
+                        width: icon.width as i32,
+                        height: icon.height as i32,
+                        mipmaps: 1,
+                        format: raylib::PixelFormat::PIXELFORMAT_UNCOMPRESSED_R8G8B8A8 as i32,
+                    });
+                }
+                ViewportCommand::Focus => raylib::SetWindowFocused(),
+                ViewportCommand::MousePassthrough(passthrough) => {
+                    set_window_flag(ConfigFlags::FLAG_WINDOW_MOUSE_PASSTHROUGH, passthrough)
+                }
+                ViewportCommand::CursorPosition(pos) => {
+                    raylib::SetMousePosition((pos.x * scale) as i32, (pos.y * scale) as i32)
+                }
+                ViewportCommand::CursorVisible(true) => raylib::ShowCursor(),
+                ViewportCommand::CursorVisible(false) => raylib::HideCursor(),
+                ViewportCommand::CursorGrab(egui::CursorGrab::None) => raylib::EnableCursor(),
+                ViewportCommand::CursorGrab(_) => raylib::DisableCursor(),
+                // the clipboard requests come back to egui as input on the next frame
+                ViewportCommand::RequestCut => self.input.raw.events.push(egui::Event::Cut),
+                ViewportCommand::RequestCopy => self.input.raw.events.push(egui::Event::Copy),
+                ViewportCommand::RequestPaste => {
+                    if let Some(event) = self.input.paste_event() {
+                        self.input.raw.events.push(event);
+                    }
+                }
+                // everything else has no raylib counterpart
+                _ => {}
+            }
+        }
+    }
+
+    fn set_cursor_icon(&mut self, cursor_icon: CursorIcon) {
+        // raylib creates a new system cursor on every call, so only touch it on changes
+        if self.cursor_icon == Some(cursor_icon) {
+            return;
+        }
+        if cursor_icon == CursorIcon::None {
+            raylib::HideCursor();
+        } else {
+            if self.cursor_icon == Some(CursorIcon::None) {
+                raylib::ShowCursor();
+            }
+            raylib::SetMouseCursor(translate_cursor(cursor_icon) as i32);
+        }
+        self.cursor_icon = Some(cursor_icon);
+    }
+
+    /// Register a raylib texture to show it in egui, see [`Painter::register_native_texture`].
+    pub fn register_native_texture(&mut self, texture: raylib::Texture2D) -> egui::TextureId {
+        self.painter.register_native_texture(texture)
+    }
+
+    /// Register a render target to show it in egui, see [`Painter::register_render_texture`].
+    pub fn register_render_texture(&mut self, target: raylib::RenderTexture2D) -> egui::TextureId {
+        self.painter.register_render_texture(target)
+    }
+
+    /// Show another raylib texture under an already registered id.
+    pub fn update_native_texture(&mut self, id: egui::TextureId, texture: raylib::Texture2D) {
+        self.painter.update_native_texture(id, texture);
+    }
+
+    /// Show a render target under an already registered id.
+    pub fn update_render_texture(&mut self, id: egui::TextureId, target: raylib::RenderTexture2D) {
+        self.painter.update_render_texture(id, target);
+    }
+
+    /// Draw a registered texture as premultiplied, see [`Painter::set_premultiplied_alpha`].
+    pub fn set_premultiplied_alpha(&mut self, id: egui::TextureId, premultiplied: bool) {
+        self.painter.set_premultiplied_alpha(id, premultiplied);
+    }
+
+    /// Unregister a raylib texture, it stays loaded.
+    pub fn free_native_texture(&mut self, id: egui::TextureId) {
+        self.painter.free_native_texture(id);
+    }
+
+    /// Draw egui into `target` instead of the window, `None` goes back to the window.
+    ///
+    /// egui is laid out for the size of the texture and the pointer has to be injected
+    /// with [`Self::set_target_pointer`], for example from a ray hitting an in-game screen,
+    /// touches likewise with [`Self::set_target_touches`]. The texture is cleared on every
+    /// `paint`.
+    ///
+    /// egui blends with premultiplied alpha, so that is what the texture ends up holding.
+    /// Draw it inside `BeginBlendMode(BLEND_ALPHA_PREMULTIPLY)`, with the default blend
+    /// mode the edges of windows and text come out too dark. To show it in another egui,
+    /// register it and mark it with [`Self::set_premultiplied_alpha`].
+    pub fn set_render_target(&mut self, target: Option<raylib::RenderTexture2D>) {
+        self.render_target = target;
+        self.input.set_target_size(
+            target.map(|t| egui::vec2(t.texture.width as f32, t.texture.height as f32)),
+        );
+    }
+
+    /// Set the pointer position in pixels of the render target, `None` when it is off it.
+    pub fn set_target_pointer(&mut self, pos: Option<egui::Pos2>) {
+        self.input.set_target_pointer(pos);
+    }
+
+    /// Set the fingers on the render target in pixels of the texture, every frame before
+    /// `run`, see [`Input::push_touches`].
+    pub fn set_target_touches(&mut self, touches: &[TouchPoint]) {
+        self.input.push_touches(touches);
     }
 
     pub fn paint(&mut self) {
+        if let Some(target) = self.render_target {
+            raylib::BeginTextureMode(target);
+            raylib::ClearBackground(Color::BLANK);
+            self.paint_shapes();
+            raylib::EndTextureMode();
+        } else {
+            self.paint_shapes();
+        }
+    }
+
+    fn paint_shapes(&mut self) {
         let shapes = std::mem::take(&mut self.shapes);
         let textures_delta = std::mem::take(&mut self.textures_delta);
         let clipped_primitives = self.ctx.tessellate(shapes, self.ctx.pixels_per_point());
@@ -46,3 +394,94 @@ impl EguiRaylib {
         );
     }
 }
+
+/// Same as [`EguiRaylib::new`].
+impl Default for EguiRaylib {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for EguiRaylib {
+    fn drop(&mut self) {
+        #[cfg(feature = "persistence")]
+        self.save();
+        // leave the cursor like egui found it
+        if raylib::IsWindowReady() && self.cursor_icon.is_some() {
+            raylib::SetMouseCursor(raylib::MouseCursor::MOUSE_CURSOR_DEFAULT as i32);
+            raylib::ShowCursor();
+        }
+    }
+}
+
+/// Outline the focused widget, egui only hints at keyboard focus which is easy to lose
+/// track of with a gamepad.
+fn paint_focus_ring(ctx: &egui::Context) {
+    let Some(rect) = focused_rect(ctx) else {
+        return;
+    };
+    let color = ctx.style().visuals.selection.stroke.color;
+    ctx.layer_painter(egui::LayerId::new(
+        egui::Order::Foreground,
+        egui::Id::new("gamepad_focus_ring"),
+    ))
+    .rect_stroke(
+        rect.expand(2.0),
+        2.0,
+        egui::Stroke::new(2.0, color),
+        egui::StrokeKind::Outside,
+    );
+}
+
+/// Where the widget with keyboard focus was drawn last frame.
+fn focused_rect(ctx: &egui::Context) -> Option<egui::Rect> {
+    ctx.memory(|mem| mem.focused())
+        .and_then(|id| ctx.read_response(id))
+        .map(|response| response.rect)
+}
+
+/// raylib takes C strings, NULs would cut them short or fail the conversion.
+fn without_nul(text: &str) -> Cow<'_, str> {
+    if text.contains('\0') {
+        Cow::Owned(text.replace('\0', ""))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+fn set_window_flag(flag: ConfigFlags, enabled: bool) {
+    if enabled {
+        raylib::SetWindowState(flag as u32);
+    } else {
+        raylib::ClearWindowState(flag as u32);
+    }
+}
+
+fn translate_cursor(cursor_icon: CursorIcon) -> raylib::MouseCursor {
+    use raylib::MouseCursor::*;
+    match cursor_icon {
+        CursorIcon::PointingHand | CursorIcon::Grab | CursorIcon::Grabbing => {
+            MOUSE_CURSOR_POINTING_HAND
+        }
+        CursorIcon::Cell | CursorIcon::Crosshair => MOUSE_CURSOR_CROSSHAIR,
+        CursorIcon::Text | CursorIcon::VerticalText => MOUSE_CURSOR_IBEAM,
+        CursorIcon::Move | CursorIcon::AllScroll => MOUSE_CURSOR_RESIZE_ALL,
+        CursorIcon::NoDrop | CursorIcon::NotAllowed => MOUSE_CURSOR_NOT_ALLOWED,
+        CursorIcon::ResizeHorizontal
+        | CursorIcon::ResizeEast
+        | CursorIcon::ResizeWest
+        | CursorIcon::ResizeColumn => MOUSE_CURSOR_RESIZE_EW,
+        CursorIcon::ResizeVertical
+        | CursorIcon::ResizeNorth
+        | CursorIcon::ResizeSouth
+        | CursorIcon::ResizeRow => MOUSE_CURSOR_RESIZE_NS,
+        CursorIcon::ResizeNeSw | CursorIcon::ResizeNorthEast | CursorIcon::ResizeSouthWest => {
+            MOUSE_CURSOR_RESIZE_NESW
+        }
+        CursorIcon::ResizeNwSe | CursorIcon::ResizeNorthWest | CursorIcon::ResizeSouthEast => {
+            MOUSE_CURSOR_RESIZE_NWSE
+        }
+        // raylib only has the standard GLFW cursors, everything else falls back to the arrow
+        _ => MOUSE_CURSOR_DEFAULT,
+    }
+}
@@ -1,30 +1,121 @@
-use crate::color::Color;
-use crate::math::Rectangle;
+use super::buffers::{StreamBuffers, Vertex};
+use super::input::render_scale;
+use super::shader::EguiShader;
+use crate::math::{Matrix, Rectangle};
 use crate::raylib;
 use crate::rlgl;
 use egui::Mesh;
-use egui::Rect;
+use egui::TextureOptions;
 use egui::epaint::Primitive;
 use egui::epaint::{ImageData, TextureId};
+use egui::{PaintCallback, PaintCallbackInfo, Rect};
 use std::collections::HashMap;
 
-#[derive(Default, Clone)]
+/// A paint callback for the raylib painter, put it in the `callback` of an `egui::PaintCallback`.
+///
+/// The closure runs in the middle of painting egui and can use any raylib or rlgl drawing.
+/// Drawing is clipped to the clip rect and the projection is orthographic over the
+/// callback rect in pixels, so 2D shapes are placed relative to its top left corner. For
+/// 3D use [`begin_mode_3d`], `BeginMode3D` would stretch the scene over the whole screen.
+/// The painter restores its state afterwards.
+pub struct CallbackFn {
+    f: Box<dyn Fn(PaintCallbackInfo) + Sync + Send>,
+}
+
+impl CallbackFn {
+    pub fn new(callback: impl Fn(PaintCallbackInfo) + Sync + Send + 'static) -> Self {
+        Self {
+            f: Box::new(callback),
+        }
+    }
+}
+
+/// One draw call, either into the shared buffers or a user callback.
+enum DrawCall {
+    Mesh {
+        clip_rect: Rect,
+        texture_id: TextureId,
+        /// The vertex the indices of this draw are relative to.
+        first_vertex: usize,
+        first_index: usize,
+        index_count: usize,
+    },
+    Callback {
+        clip_rect: Rect,
+        callback: PaintCallback,
+    },
+}
+
+/// A texture egui asked for, owned by the painter.
+struct ManagedTexture {
+    texture: raylib::Texture2D,
+    options: TextureOptions,
+}
+
+/// A texture owned by the caller, shown in egui through a `TextureId::User`.
+struct NativeTexture {
+    texture: raylib::Texture2D,
+    /// Render textures are stored upside down.
+    flip_y: bool,
+    /// raylib textures have straight alpha, the painter premultiplies them while drawing.
+    premultiplied: bool,
+}
+
+/// Draws egui output with rlgl.
+///
+/// The painter owns the GPU textures egui asks for and its vertex buffers, they are
+/// released when egui frees them, on `destroy` or when the painter is dropped.
+#[derive(Default)]
 pub struct Painter {
-    textures: HashMap<TextureId, raylib::Texture2D>,
-    material: raylib::Material,
+    textures: HashMap<TextureId, ManagedTexture>,
+    native_textures: HashMap<u64, NativeTexture>,
+    next_native_id: u64,
+    // Created on the first paint, these need an OpenGL context.
+    buffers: Option<StreamBuffers>,
+    shader: Option<EguiShader>,
+    linear_output: bool,
+    // Frame data, kept around to reuse the allocations.
+    vertices: Vec<Vertex>,
+    indices: Vec<u16>,
+    draw_calls: Vec<DrawCall>,
 }
 impl Painter {
     pub fn new() -> Self {
-        Self {
-            textures: Default::default(),
-            material: raylib::LoadMaterialDefault(),
-        }
+        Self::default()
     }
+
+    /// Release all GPU resources, the painter can still be used afterwards.
+    ///
+    /// Does nothing once the window is closed, raylib already dropped the OpenGL
+    /// context and everything in it.
     pub fn destroy(&mut self) {
-        for tex in &self.textures {
-            raylib::UnloadTexture(*tex.1);
+        if !raylib::IsWindowReady() {
+            self.textures.clear();
+            self.buffers = None;
+            self.shader = None;
+            return;
         }
-        raylib::UnloadMaterial(self.material);
+        for (_, managed) in self.textures.drain() {
+            unload_texture(managed.texture);
+        }
+        if let Some(mut buffers) = self.buffers.take() {
+            buffers.destroy();
+        }
+        if let Some(shader) = self.shader.take() {
+            shader.unload();
+        }
+    }
+
+    /// Convert the output to linear colors, for framebuffers with sRGB encoding enabled.
+    ///
+    /// egui blends in gamma space, by default the colors are written as they are.
+    pub fn set_linear_output(&mut self, linear_output: bool) {
+        self.linear_output = linear_output;
+    }
+
+    /// Number of GPU textures the painter currently holds.
+    pub fn live_textures(&self) -> usize {
+        self.textures.len()
     }
 
     pub fn paint_and_update_textures(
@@ -40,7 +131,6 @@ impl Painter {
         self.paint_primitives(pixels_per_point, clipped_primitives);
 
         for &id in &textures_delta.free {
-            println!("free texture!");
             self.free_texture(id);
         }
     }
@@ -50,6 +140,10 @@ impl Painter {
         pixels_per_point: f32,
         clipped_primitives: &[egui::ClippedPrimitive],
     ) {
+        self.vertices.clear();
+        self.indices.clear();
+        self.draw_calls.clear();
+
         for egui::ClippedPrimitive {
             clip_rect,
             primitive,
@@ -57,77 +151,172 @@ impl Painter {
         {
             match primitive {
                 Primitive::Mesh(mesh) => {
-                    self.paint_mesh(pixels_per_point, clip_rect, mesh);
-                }
-                Primitive::Callback(_) => {
-                    panic!("Custom rendering callbacks are not implemented in egui_glium");
+                    self.add_mesh(*clip_rect, mesh);
                 }
+                Primitive::Callback(callback) => self.draw_calls.push(DrawCall::Callback {
+                    clip_rect: *clip_rect,
+                    callback: callback.clone(),
+                }),
             }
         }
-    }
 
-    // A new helper function to convert egui mesh to a raylib mesh and draw it
-    fn draw_egui_mesh(&mut self, egui_mesh: &egui::Mesh) {
-        if egui_mesh.indices.is_empty() || egui_mesh.vertices.is_empty() {
+        if self.draw_calls.is_empty() {
             return;
         }
 
-        let texture = self
-            .textures
-            .get(&egui_mesh.texture_id)
-            .expect("Texture not found for mesh");
-
-        let mut positions: Vec<[f32; 3]> = Vec::with_capacity(egui_mesh.vertices.len());
-        let mut uvs: Vec<[f32; 2]> = Vec::with_capacity(egui_mesh.vertices.len());
-        let mut colors: Vec<[u8; 4]> = Vec::with_capacity(egui_mesh.vertices.len());
-        let indices: Vec<u16> = egui_mesh.indices.iter().map(|&i| i as u16).collect();
-
-        for v in &egui_mesh.vertices {
-            positions.push([v.pos.x, v.pos.y, 0.0]);
-            uvs.push([v.uv.x, v.uv.y]);
-            colors.push(v.color.to_srgba_unmultiplied());
-        }
-
-        let mut vbo_id_array = [0u32; 7];
-        let mut mesh = raylib::Mesh {
-            vertexCount: egui_mesh.vertices.len() as i32,
-            triangleCount: (egui_mesh.indices.len() / 3) as i32,
-            vertices: positions.as_mut_ptr() as *mut f32,
-            texcoords: uvs.as_mut_ptr() as *mut f32,
-            colors: colors.as_mut_ptr() as *mut u8,
-            indices: indices.as_ptr() as *mut u16,
-            vboId: vbo_id_array.as_mut_ptr(),
-            ..Default::default()
+        let mut buffers = self.buffers.take().unwrap_or_else(StreamBuffers::new);
+        // a frame of only callbacks has no mesh draw calls, and maybe no GPU buffers yet
+        if !self.vertices.is_empty() {
+            buffers.upload(&self.vertices, &self.indices);
+        }
+        let shader = self.shader.take().unwrap_or_else(EguiShader::load);
+        // egui meshes are in points, raylib draws in screen units, or in pixels when
+        // drawing into a render texture
+        let render_scale = match rlgl::get_active_framebuffer() {
+            0 => render_scale(),
+            _ => 1.0,
         };
+        let screen_scale = pixels_per_point / render_scale;
+        let mvp = raylib::MatrixMultiply(
+            raylib::MatrixScale(screen_scale, screen_scale, 1.0),
+            raylib::MatrixMultiply(
+                raylib::MatrixMultiply(rlgl::get_matrix_transform(), rlgl::get_matrix_modelview()),
+                rlgl::get_matrix_projection(),
+            ),
+        );
+
+        rlgl::rlDisableBackfaceCulling();
+        rlgl::rlDisableDepthTest();
+
+        for draw_call in &self.draw_calls {
+            match *draw_call {
+                DrawCall::Mesh {
+                    clip_rect,
+                    texture_id,
+                    first_vertex,
+                    first_index,
+                    index_count,
+                } => {
+                    let Some((texture, premultiplied)) = self.texture(texture_id) else {
+                        continue;
+                    };
+
+                    // Clipping flushes raylib's own batch, which resets the bound GL state,
+                    // so everything is bound again for every draw call.
+                    if !begin_clip(pixels_per_point, clip_rect) {
+                        continue;
+                    }
 
-        raylib::UploadMesh(&mut mesh, true);
+                    shader.begin(mvp, self.linear_output);
+                    shader.set_texture(texture.id, !premultiplied);
 
-        std::mem::forget(positions);
-        std::mem::forget(uvs);
-        std::mem::forget(colors);
-        std::mem::forget(indices);
+                    buffers.bind(&shader.shader, first_vertex);
+                    rlgl::draw_vertex_array_elements(
+                        first_index as i32,
+                        index_count as i32,
+                        std::ptr::null(),
+                    );
+                    buffers.unbind();
 
-        raylib::SetMaterialTexture(&mut self.material, 0, *texture);
+                    shader.end();
 
-        raylib::DrawMesh(mesh, self.material, raylib::MatrixIdentity());
-        raylib::UnloadMesh(mesh);
+                    raylib::EndScissorMode();
+                }
+                DrawCall::Callback {
+                    clip_rect,
+                    ref callback,
+                } => paint_callback(pixels_per_point, clip_rect, callback),
+            }
+        }
+
+        rlgl::rlSetBlendMode(rlgl::rlBlendMode::RL_BLEND_ALPHA as i32);
+        rlgl::rlEnableBackfaceCulling();
+        self.buffers = Some(buffers);
+        self.shader = Some(shader);
     }
 
-    // Your existing paint_mesh function gets much simpler
-    fn paint_mesh(&mut self, pixels_per_point: f32, clip_rect: &Rect, mesh: &Mesh) {
+    /// Append an egui mesh to the frame data, splitting it if it does not fit 16 bit indices.
+    fn add_mesh(&mut self, clip_rect: Rect, mesh: &Mesh) {
         debug_assert!(mesh.is_valid());
+        if mesh.vertices.len() <= u16::MAX as usize {
+            self.add_vertices(
+                clip_rect,
+                mesh.texture_id,
+                &mesh.vertices,
+                mesh.indices.iter().copied(),
+            );
+            return;
+        }
 
-        rlgl::rlDisableBackfaceCulling();
-        rlgl::rlDisableDepthTest();
+        // Like `Mesh::split_to_u16`, without copying the mesh: take as many triangles as
+        // fit into a range of u16::MAX vertices and draw them as one piece.
+        let mut start = 0;
+        while start < mesh.indices.len() {
+            let (mut min, mut max) = (u32::MAX, 0);
+            let mut end = start;
+            for triangle in mesh.indices[start..].chunks_exact(3) {
+                let new_min = triangle.iter().fold(min, |min, &i| min.min(i));
+                let new_max = triangle.iter().fold(max, |max, &i| max.max(i));
+                if new_max - new_min >= u16::MAX as u32 {
+                    break;
+                }
+                (min, max) = (new_min, new_max);
+                end += 3;
+            }
+            assert!(end > start, "a triangle spans more than 65535 vertices");
+            self.add_vertices(
+                clip_rect,
+                mesh.texture_id,
+                &mesh.vertices[min as usize..=max as usize],
+                mesh.indices[start..end].iter().map(|&i| i - min),
+            );
+            start = end;
+        }
+    }
 
-        let clip_min_x = (pixels_per_point * clip_rect.min.x).round() as i32;
-        let clip_min_y = (pixels_per_point * clip_rect.min.y).round() as i32;
-        let clip_max_x = (pixels_per_point * clip_rect.max.x).round() as i32;
-        let clip_max_y = (pixels_per_point * clip_rect.max.y).round() as i32;
+    fn add_vertices(
+        &mut self,
+        clip_rect: Rect,
+        texture_id: TextureId,
+        vertices: &[egui::epaint::Vertex],
+        indices: impl ExactSizeIterator<Item = u32>,
+    ) {
+        if indices.len() == 0 || vertices.is_empty() {
+            return;
+        }
 
-        raylib::BeginScissorMode(clip_min_x, clip_min_y, clip_max_x, clip_max_y);
-        self.draw_egui_mesh(mesh);
-        raylib::EndScissorMode();
+        // Indices are 16 bit, start a new range of vertices once they would overflow.
+        let last_first_vertex =
+            self.draw_calls
+                .iter()
+                .rev()
+                .find_map(|draw_call| match draw_call {
+                    DrawCall::Mesh { first_vertex, .. } => Some(*first_vertex),
+                    DrawCall::Callback { .. } => None,
+                });
+        let first_vertex = match last_first_vertex {
+            Some(last) if self.vertices.len() - last + vertices.len() <= u16::MAX as usize => last,
+            _ => self.vertices.len(),
+        };
+        let base = (self.vertices.len() - first_vertex) as u32;
+
+        self.draw_calls.push(DrawCall::Mesh {
+            clip_rect,
+            texture_id,
+            first_vertex,
+            first_index: self.indices.len(),
+            index_count: indices.len(),
+        });
+        let flip_y = match texture_id {
+            TextureId::User(id) => self.native_textures.get(&id).is_some_and(|n| n.flip_y),
+            TextureId::Managed(_) => false,
+        };
+        self.vertices.extend(vertices.iter().map(|v| Vertex {
+            pos: [v.pos.x, v.pos.y],
+            uv: [v.uv.x, if flip_y { 1.0 - v.uv.y } else { v.uv.y }],
+            color: v.color.to_array(),
+        }));
+        self.indices.extend(indices.map(|i| (base + i) as u16));
     }
 
     pub fn set_texture(&mut self, tex_id: egui::TextureId, delta: &egui::epaint::ImageDelta) {
@@ -137,54 +326,406 @@ impl Painter {
             ImageData::Color(color_data) => color_data
                 .pixels
                 .iter()
-                .flat_map(|c| c.to_srgba_unmultiplied())
+                .flat_map(|c| c.to_array())
                 .collect::<Vec<u8>>(),
         };
 
-        let image = raylib::Image {
-            data: pixels.as_ptr() as *mut std::ffi::c_void,
-            width: size[0] as i32,
-            height: size[1] as i32,
-            mipmaps: 1,
-            format: rlgl::rlPixelFormat::RL_PIXELFORMAT_UNCOMPRESSED_R8G8B8A8 as i32,
+        let rec = |x: usize, y: usize| Rectangle {
+            x: x as f32,
+            y: y as f32,
+            width: size[0] as f32,
+            height: size[1] as f32,
         };
 
-        if let Some(pos) = delta.pos {
-            if let Some(user_texture) = self.textures.get_mut(&tex_id) {
-                let mut orig = raylib::LoadImageFromTexture(*user_texture);
-                raylib::ImageDraw(
-                    &mut orig,
-                    image,
-                    Rectangle {
-                        x: 0.0,
-                        y: 0.0,
-                        width: size[0] as f32,
-                        height: size[1] as f32,
-                    },
-                    Rectangle {
-                        x: pos[0] as f32,
-                        y: pos[1] as f32,
-                        width: size[0] as f32,
-                        height: size[1] as f32,
-                    },
-                    Color::WHITE,
+        match (delta.pos, self.textures.get_mut(&tex_id)) {
+            // partial update, the font atlas gets a patch for every new glyph
+            (Some([x, y]), Some(managed)) => {
+                let texture = &mut managed.texture;
+                debug_assert!(
+                    x + size[0] <= texture.width as usize && y + size[1] <= texture.height as usize,
+                    "texture patch out of bounds"
                 );
-
-                let new_texture = raylib::LoadTextureFromImage(orig);
-                self.textures.insert(tex_id, new_texture);
-
-                // //TODO
-                // raylib::ExportImage(orig, &format!("file-{}.png", new_texture.id));
+                raylib::UpdateTextureRec(*texture, rec(x, y), &pixels);
+                if managed.options != delta.options {
+                    managed.options = delta.options;
+                    apply_texture_options(texture, delta.options);
+                } else if delta.options.mipmap_mode.is_some() {
+                    raylib::GenTextureMipmaps(texture);
+                }
+            }
+            (Some(_), None) => {}
+            // same size as before, overwrite in place
+            (None, Some(managed))
+                if managed.texture.width as usize == size[0]
+                    && managed.texture.height as usize == size[1] =>
+            {
+                raylib::UpdateTextureRec(managed.texture, rec(0, 0), &pixels);
+                managed.options = delta.options;
+                apply_texture_options(&mut managed.texture, delta.options);
+            }
+            // new texture, or one that changed size like a growing font atlas
+            (None, _) => {
+                let image = raylib::Image {
+                    data: pixels.as_ptr() as *mut std::ffi::c_void,
+                    width: size[0] as i32,
+                    height: size[1] as i32,
+                    mipmaps: 1,
+                    format: rlgl::rlPixelFormat::RL_PIXELFORMAT_UNCOMPRESSED_R8G8B8A8 as i32,
+                };
+                // raylib copies the pixels to the GPU, the image stays owned by `pixels`
+                let mut texture = raylib::LoadTextureFromImage(image);
+                apply_texture_options(&mut texture, delta.options);
+                let managed = ManagedTexture {
+                    texture,
+                    options: delta.options,
+                };
+                if let Some(old) = self.textures.insert(tex_id, managed) {
+                    unload_texture(old.texture);
+                }
             }
-        } else {
-            let tex = raylib::LoadTextureFromImage(image);
-            self.textures.insert(tex_id, tex);
-            //TODO
-            // raylib::ExportImage(image, &format!("file-{}.png", tex.id));
         }
     }
 
     pub fn free_texture(&mut self, tex_id: egui::TextureId) {
-        self.textures.remove(&tex_id);
+        if let Some(managed) = self.textures.remove(&tex_id) {
+            unload_texture(managed.texture);
+        }
+    }
+
+    /// Make a raylib texture available to egui, for example with `ui.image`.
+    ///
+    /// The texture stays owned by the caller and must outlive its use in egui, free the id
+    /// with [`Self::free_native_texture`] before unloading it.
+    pub fn register_native_texture(&mut self, texture: raylib::Texture2D) -> TextureId {
+        self.add_native_texture(texture, false)
+    }
+
+    /// Make the color buffer of a render texture available to egui.
+    ///
+    /// OpenGL stores render textures upside down, the painter flips them back.
+    pub fn register_render_texture(&mut self, target: raylib::RenderTexture2D) -> TextureId {
+        self.add_native_texture(target.texture, true)
+    }
+
+    /// Show another raylib texture under an already registered id.
+    pub fn update_native_texture(&mut self, id: TextureId, texture: raylib::Texture2D) {
+        self.set_native_texture(id, texture, false);
+    }
+
+    /// Show the color buffer of a render texture under an already registered id.
+    pub fn update_render_texture(&mut self, id: TextureId, target: raylib::RenderTexture2D) {
+        self.set_native_texture(id, target.texture, true);
+    }
+
+    /// Mark a registered texture as holding premultiplied alpha, so it is drawn as it is.
+    ///
+    /// Use it for a render texture egui drew into, see `EguiRaylib::set_render_target`.
+    /// Updating the id to another texture goes back to straight alpha.
+    pub fn set_premultiplied_alpha(&mut self, id: TextureId, premultiplied: bool) {
+        if let TextureId::User(id) = id
+            && let Some(native) = self.native_textures.get_mut(&id)
+        {
+            native.premultiplied = premultiplied;
+        }
+    }
+
+    /// Forget a registered texture, the texture itself is not unloaded.
+    pub fn free_native_texture(&mut self, id: TextureId) {
+        if let TextureId::User(id) = id {
+            self.native_textures.remove(&id);
+        }
+    }
+
+    fn add_native_texture(&mut self, texture: raylib::Texture2D, flip_y: bool) -> TextureId {
+        let id = self.next_native_id;
+        self.next_native_id += 1;
+        self.native_textures.insert(
+            id,
+            NativeTexture {
+                texture,
+                flip_y,
+                premultiplied: false,
+            },
+        );
+        TextureId::User(id)
+    }
+
+    fn set_native_texture(&mut self, id: TextureId, texture: raylib::Texture2D, flip_y: bool) {
+        if let TextureId::User(id) = id
+            && let Some(native) = self.native_textures.get_mut(&id)
+        {
+            *native = NativeTexture {
+                texture,
+                flip_y,
+                premultiplied: false,
+            };
+        }
+    }
+
+    /// The texture behind an id, and whether its colors are premultiplied already.
+    fn texture(&self, id: TextureId) -> Option<(&raylib::Texture2D, bool)> {
+        match id {
+            TextureId::Managed(_) => self.textures.get(&id).map(|m| (&m.texture, true)),
+            TextureId::User(id) => self
+                .native_textures
+                .get(&id)
+                .map(|n| (&n.texture, n.premultiplied)),
+        }
+    }
+}
+
+impl Drop for Painter {
+    fn drop(&mut self) {
+        self.destroy();
+    }
+}
+
+fn unload_texture(texture: raylib::Texture2D) {
+    // id 0 is a texture that failed to load, there is nothing on the GPU to free
+    if texture.id != 0 {
+        raylib::UnloadTexture(texture);
+    }
+}
+
+/// Set the sampling of a texture to match egui's options, this also (re)generates mipmaps.
+fn apply_texture_options(texture: &mut raylib::Texture2D, options: TextureOptions) {
+    use egui::TextureFilter::{Linear, Nearest};
+
+    if options.mipmap_mode.is_some() {
+        raylib::GenTextureMipmaps(texture);
+    }
+
+    // raylib's filter presets set both directions at once, egui picks them separately
+    let mag_filter = match options.magnification {
+        Nearest => rlgl::TEXTURE_FILTER_NEAREST,
+        Linear => rlgl::TEXTURE_FILTER_LINEAR,
+    };
+    let min_filter = match (options.minification, options.mipmap_mode) {
+        (Nearest, None) => rlgl::TEXTURE_FILTER_NEAREST,
+        (Linear, None) => rlgl::TEXTURE_FILTER_LINEAR,
+        (Nearest, Some(Nearest)) => rlgl::TEXTURE_FILTER_MIP_NEAREST,
+        (Linear, Some(Nearest)) => rlgl::TEXTURE_FILTER_LINEAR_MIP_NEAREST,
+        (Nearest, Some(Linear)) => rlgl::TEXTURE_FILTER_NEAREST_MIP_LINEAR,
+        (Linear, Some(Linear)) => rlgl::TEXTURE_FILTER_MIP_LINEAR,
+    };
+    rlgl::texture_parameters(texture.id, rlgl::TEXTURE_MAG_FILTER, mag_filter);
+    rlgl::texture_parameters(texture.id, rlgl::TEXTURE_MIN_FILTER, min_filter);
+
+    let wrap = match options.wrap_mode {
+        egui::TextureWrapMode::ClampToEdge => raylib::TextureWrap::TEXTURE_WRAP_CLAMP,
+        egui::TextureWrapMode::Repeat => raylib::TextureWrap::TEXTURE_WRAP_REPEAT,
+        egui::TextureWrapMode::MirroredRepeat => raylib::TextureWrap::TEXTURE_WRAP_MIRROR_REPEAT,
+    };
+    raylib::SetTextureWrap(*texture, wrap as i32);
+}
+
+/// Start clipping to `clip_rect`, given in points, returns false if nothing is visible.
+///
+/// raylib's `BeginScissorMode` takes screen coordinates and only accounts for HiDPI on
+/// some platforms, so this sets the scissor in framebuffer pixels itself.
+fn begin_clip(pixels_per_point: f32, clip_rect: Rect) -> bool {
+    let framebuffer_width = rlgl::get_framebuffer_width();
+    let framebuffer_height = rlgl::get_framebuffer_height();
+    let to_pixels =
+        |points: f32, max: i32| ((pixels_per_point * points).round() as i32).clamp(0, max);
+
+    let clip_min_x = to_pixels(clip_rect.min.x, framebuffer_width);
+    let clip_min_y = to_pixels(clip_rect.min.y, framebuffer_height);
+    let clip_max_x = to_pixels(clip_rect.max.x, framebuffer_width);
+    let clip_max_y = to_pixels(clip_rect.max.y, framebuffer_height);
+    if clip_max_x <= clip_min_x || clip_max_y <= clip_min_y {
+        return false;
+    }
+
+    rlgl::rlDrawRenderBatchActive();
+    rlgl::rlEnableScissorTest();
+    // OpenGL counts from the bottom of the framebuffer
+    rlgl::scissor(
+        clip_min_x,
+        framebuffer_height - clip_max_y,
+        clip_max_x - clip_min_x,
+        clip_max_y - clip_min_y,
+    );
+    true
+}
+
+/// Run a [`CallbackFn`] with the viewport and projection set to its rect.
+fn paint_callback(pixels_per_point: f32, clip_rect: Rect, callback: &PaintCallback) {
+    // other integrations' callbacks can't do anything useful here
+    let Some(callback_fn) = callback.callback.downcast_ref::<CallbackFn>() else {
+        return;
+    };
+    let framebuffer_width = rlgl::get_framebuffer_width();
+    let framebuffer_height = rlgl::get_framebuffer_height();
+    let info = PaintCallbackInfo {
+        viewport: callback.rect,
+        clip_rect,
+        pixels_per_point,
+        screen_size_px: [framebuffer_width as u32, framebuffer_height as u32],
+    };
+    let viewport = info.viewport_in_pixels();
+    if viewport.width_px <= 0 || viewport.height_px <= 0 {
+        return;
+    }
+    if !begin_clip(pixels_per_point, clip_rect) {
+        return;
+    }
+    // raylib's viewport stays as it is, it may be offset for letterboxing, the projection
+    // maps into the callback rect instead
+    rlgl::rlMatrixMode(rlgl::PROJECTION);
+    rlgl::rlPushMatrix();
+    rlgl::set_matrix_projection(raylib::MatrixMultiply(
+        raylib::MatrixOrtho(
+            0.0,
+            viewport.width_px as f64,
+            viewport.height_px as f64,
+            0.0,
+            0.0,
+            1.0,
+        ),
+        viewport_transform(&info),
+    ));
+    rlgl::rlMatrixMode(rlgl::MODELVIEW);
+    rlgl::rlPushMatrix();
+    rlgl::rlLoadIdentity();
+
+    (callback_fn.f)(info);
+
+    // draw whatever the callback left in raylib's batch while its state is still active
+    rlgl::rlDrawRenderBatchActive();
+    rlgl::rlMatrixMode(rlgl::PROJECTION);
+    rlgl::rlPopMatrix();
+    rlgl::rlMatrixMode(rlgl::MODELVIEW);
+    rlgl::rlPopMatrix();
+    rlgl::rlSetBlendMode(rlgl::rlBlendMode::RL_BLEND_ALPHA as i32);
+    rlgl::rlDisableBackfaceCulling();
+    rlgl::rlDisableDepthTest();
+    raylib::EndScissorMode();
+}
+
+/// Start 3D drawing in a [`CallbackFn`], like `BeginMode3D` but with the projection fitted
+/// to the callback rect, end it with `EndMode3D` as usual.
+///
+/// `BeginMode3D` takes the aspect ratio from the whole framebuffer and covers all of it,
+/// which stretches the scene inside the rect.
+pub fn begin_mode_3d(info: &PaintCallbackInfo, camera: raylib::Camera3D) {
+    raylib::BeginMode3D(camera);
+    let viewport = info.viewport_in_pixels();
+    let aspect = viewport.width_px as f64 / viewport.height_px as f64;
+    let near = rlgl::rlGetCullDistanceNear();
+    let far = rlgl::rlGetCullDistanceFar();
+    let projection = if camera.projection == raylib::CameraProjection::CAMERA_PERSPECTIVE as i32 {
+        raylib::MatrixPerspective((camera.fovy as f64).to_radians(), aspect, near, far)
+    } else {
+        let top = camera.fovy as f64 / 2.0;
+        let right = top * aspect;
+        raylib::MatrixOrtho(-right, right, -top, top, near, far)
+    };
+    rlgl::set_matrix_projection(raylib::MatrixMultiply(projection, viewport_transform(info)));
+}
+
+/// Squeeze clip space into the callback rect, in place of setting a viewport for it.
+fn viewport_transform(info: &PaintCallbackInfo) -> Matrix {
+    let viewport = info.viewport_in_pixels();
+    let [screen_width, screen_height] = info.screen_size_px.map(|size| size as f32);
+    let width = viewport.width_px as f32;
+    let height = viewport.height_px as f32;
+    let center_x = (viewport.left_px as f32 + 0.5 * width) / screen_width * 2.0 - 1.0;
+    let center_y = 1.0 - (viewport.top_px as f32 + 0.5 * height) / screen_height * 2.0;
+    raylib::MatrixMultiply(
+        raylib::MatrixScale(width / screen_width, height / screen_height, 1.0),
+        raylib::MatrixTranslate(center_x, center_y, 0.0),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui::{Color32, pos2, vec2};
+
+    fn quads(count: usize) -> Mesh {
+        let mut mesh = Mesh::default();
+        for i in 0..count {
+            let rect = Rect::from_min_size(pos2(i as f32, count as f32), vec2(1.0, 1.0));
+            mesh.add_colored_rect(rect, Color32::WHITE);
+        }
+        mesh
+    }
+
+    fn managed(size: i32) -> ManagedTexture {
+        ManagedTexture {
+            texture: raylib::Texture2D {
+                id: 0,
+                width: size,
+                height: size,
+                mipmaps: 1,
+                format: rlgl::rlPixelFormat::RL_PIXELFORMAT_UNCOMPRESSED_R8G8B8A8 as i32,
+            },
+            options: TextureOptions::default(),
+        }
+    }
+
+    #[test]
+    fn live_textures_follow_set_and_free() {
+        let mut painter = Painter::default();
+        assert_eq!(painter.live_textures(), 0);
+        painter.textures.insert(TextureId::Managed(1), managed(4));
+        painter.textures.insert(TextureId::Managed(2), managed(8));
+        assert_eq!(painter.live_textures(), 2);
+
+        // a patch for a texture the painter never got is dropped
+        let patch = egui::epaint::ImageDelta::partial(
+            [0, 0],
+            egui::ColorImage::filled([1, 1], Color32::WHITE),
+            TextureOptions::default(),
+        );
+        painter.set_texture(TextureId::Managed(3), &patch);
+        assert_eq!(painter.live_textures(), 2);
+
+        painter.free_texture(TextureId::Managed(1));
+        assert_eq!(painter.live_textures(), 1);
+        painter.free_texture(TextureId::Managed(1));
+        painter.free_texture(TextureId::User(2));
+        assert_eq!(painter.live_textures(), 1);
+        painter.free_texture(TextureId::Managed(2));
+        assert_eq!(painter.live_textures(), 0);
+    }
+
+    #[test]
+    fn add_mesh_keeps_triangles_within_u16_indices() {
+        let meshes = [quads(3), quads(20_000), quads(1), quads(16_000), quads(5)];
+        assert!(meshes[1].vertices.len() > u16::MAX as usize);
+
+        let mut painter = Painter::default();
+        for mesh in &meshes {
+            painter.add_mesh(Rect::EVERYTHING, mesh);
+        }
+
+        let expected: Vec<[f32; 2]> = meshes
+            .iter()
+            .flat_map(|mesh| mesh.indices.iter().map(|&i| mesh.vertices[i as usize].pos))
+            .map(|pos| [pos.x, pos.y])
+            .collect();
+        let mut drawn = Vec::new();
+        let mut triangles = 0;
+        for draw_call in &painter.draw_calls {
+            match *draw_call {
+                DrawCall::Mesh {
+                    first_vertex,
+                    first_index,
+                    index_count,
+                    ..
+                } => {
+                    triangles += index_count / 3;
+                    for &index in &painter.indices[first_index..first_index + index_count] {
+                        assert!(index < u16::MAX);
+                        drawn.push(painter.vertices[first_vertex + index as usize].pos);
+                    }
+                }
+                DrawCall::Callback { .. } => unreachable!(),
+            }
+        }
+        assert_eq!(triangles, expected.len() / 3);
+        assert!(drawn == expected, "triangles point at the wrong vertices");
     }
 }
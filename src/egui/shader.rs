@@ -0,0 +1,102 @@
+// shader.rs
+
+use super::buffers::shader_location;
+use crate::math::Matrix;
+use crate::raylib;
+use crate::raylib::Shader;
+use crate::rlgl;
+use crate::rlgl::{rlGlVersion, rlShaderLocationIndex, rlShaderUniformDataType};
+
+const VERTEX_SHADER: &str = include_str!("egui.vs");
+const FRAGMENT_SHADER: &str = include_str!("egui.fs");
+
+/// The shader egui meshes are drawn with, it expects premultiplied colors.
+pub(crate) struct EguiShader {
+    pub shader: Shader,
+    premultiply_texture_loc: i32,
+    linear_output_loc: i32,
+}
+
+impl EguiShader {
+    pub fn load() -> Self {
+        let header = shader_header();
+        let shader = raylib::LoadShaderFromMemory(
+            &format!("{header}{VERTEX_SHADER}"),
+            &format!("{header}{FRAGMENT_SHADER}"),
+        );
+        Self {
+            shader,
+            premultiply_texture_loc: raylib::GetShaderLocation(shader, "premultiplyTexture"),
+            linear_output_loc: raylib::GetShaderLocation(shader, "linearOutput"),
+        }
+    }
+
+    /// Enable the shader and set up premultiplied alpha blending.
+    pub fn begin(&self, mvp: Matrix, linear_output: bool) {
+        rlgl::rlSetBlendFactors(
+            rlgl::RL_ONE,
+            rlgl::RL_ONE_MINUS_SRC_ALPHA,
+            rlgl::RL_FUNC_ADD,
+        );
+        rlgl::rlSetBlendMode(rlgl::rlBlendMode::RL_BLEND_CUSTOM as i32);
+
+        rlgl::rlEnableShader(self.shader.id);
+        rlgl::set_uniform_matrix(
+            shader_location(
+                &self.shader,
+                rlShaderLocationIndex::RL_SHADER_LOC_MATRIX_MVP,
+            ),
+            mvp,
+        );
+        rlgl::set_uniform(
+            shader_location(
+                &self.shader,
+                rlShaderLocationIndex::RL_SHADER_LOC_MAP_ALBEDO,
+            ),
+            &[0i32],
+            rlShaderUniformDataType::RL_SHADER_UNIFORM_INT as i32,
+        );
+        rlgl::set_uniform(
+            self.linear_output_loc,
+            &[linear_output as i32],
+            rlShaderUniformDataType::RL_SHADER_UNIFORM_INT as i32,
+        );
+    }
+
+    /// Bind the texture to sample, `premultiply` for textures with straight alpha.
+    pub fn set_texture(&self, texture_id: u32, premultiply: bool) {
+        rlgl::set_uniform(
+            self.premultiply_texture_loc,
+            &[premultiply as i32],
+            rlShaderUniformDataType::RL_SHADER_UNIFORM_INT as i32,
+        );
+        rlgl::active_texture_slot(0);
+        rlgl::enable_texture(texture_id);
+    }
+
+    /// Disable the shader, blending stays premultiplied until raylib's default is restored.
+    pub fn end(&self) {
+        rlgl::disable_texture();
+        rlgl::rlDisableShader();
+    }
+
+    pub fn unload(&self) {
+        raylib::UnloadShader(self.shader);
+    }
+}
+
+/// GLSL version and interface for the OpenGL version raylib was built for.
+fn shader_header() -> &'static str {
+    match rlgl::get_version() {
+        v if v == rlGlVersion::RL_OPENGL_ES_20 as i32 => {
+            "#version 100\n#define NEW_SHADER_INTERFACE 0\nprecision mediump float;\n"
+        }
+        v if v == rlGlVersion::RL_OPENGL_ES_30 as i32 => {
+            "#version 300 es\n#define NEW_SHADER_INTERFACE 1\nprecision mediump float;\n"
+        }
+        v if v == rlGlVersion::RL_OPENGL_21 as i32 => {
+            "#version 120\n#define NEW_SHADER_INTERFACE 0\n"
+        }
+        _ => "#version 330\n#define NEW_SHADER_INTERFACE 1\n",
+    }
+}
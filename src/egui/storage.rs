@@ -0,0 +1,127 @@
+// storage.rs
+
+use std::collections::HashMap;
+
+use crate::raylib;
+
+/// The key egui's memory is saved under.
+pub const MEMORY_KEY: &str = "egui_memory";
+
+/// Somewhere to keep text between runs, used to persist egui's memory.
+///
+/// [`FileStorage`] is the usual choice, [`MemoryStorage`] keeps everything in a map which
+/// is handy for tests.
+pub trait Storage {
+    fn get_string(&self, key: &str) -> Option<String>;
+
+    fn set_string(&mut self, key: &str, value: String);
+
+    /// Called after a save, for stores that write out in batches.
+    fn flush(&mut self) {}
+}
+
+/// Keeps every key in its own text file, read and written with raylib's file functions.
+pub struct FileStorage {
+    dir: String,
+}
+
+impl FileStorage {
+    /// Put the files into `dir`, it has to exist already, "." is the working directory.
+    pub fn new(dir: &str) -> Self {
+        Self {
+            dir: dir.trim_end_matches('/').to_owned(),
+        }
+    }
+
+    /// The file `key` is stored in.
+    pub fn file_name(&self, key: &str) -> String {
+        format!("{}/{key}.ron", self.dir)
+    }
+}
+
+impl Storage for FileStorage {
+    fn get_string(&self, key: &str) -> Option<String> {
+        // nothing saved yet is not worth raylib's "failed to open text file" warning
+        let file_name = self.file_name(key);
+        if !raylib::FileExists(&file_name) {
+            return None;
+        }
+        raylib::LoadFileText(&file_name)
+    }
+
+    fn set_string(&mut self, key: &str, value: String) {
+        let file_name = self.file_name(key);
+        if !raylib::SaveFileText(&file_name, &value) {
+            log_warning(&format!("failed to save {key} to {file_name}"));
+        }
+    }
+}
+
+/// Keeps everything in memory, nothing survives the process.
+/// ```rust
+/// use raylib_egui_rs::egui::{MemoryStorage, Storage};
+///
+/// let mut storage = MemoryStorage::default();
+/// storage.set_string("layout", "left".to_owned());
+/// assert_eq!(storage.get_string("layout").as_deref(), Some("left"));
+/// assert_eq!(storage.get_string("missing"), None);
+/// ```
+#[derive(Default, Clone, Debug)]
+pub struct MemoryStorage {
+    values: HashMap<String, String>,
+}
+
+impl Storage for MemoryStorage {
+    fn get_string(&self, key: &str) -> Option<String> {
+        self.values.get(key).cloned()
+    }
+
+    fn set_string(&mut self, key: &str, value: String) {
+        self.values.insert(key.to_owned(), value);
+    }
+}
+
+/// Save the persistent part of egui's memory, window positions, collapsed headers,
+/// scroll offsets and the like, under [`MEMORY_KEY`].
+pub fn save_memory(ctx: &egui::Context, storage: &mut dyn Storage) -> Result<(), String> {
+    let text = ctx
+        .memory(ron::to_string)
+        .map_err(|err| format!("failed to serialize egui memory: {err}"))?;
+    storage.set_string(MEMORY_KEY, text);
+    storage.flush();
+    Ok(())
+}
+
+/// Restore egui's memory saved by [`save_memory`], `Ok(false)` if nothing was saved yet.
+///
+/// Do this before setting fonts, pending font changes are part of the memory that gets
+/// replaced.
+/// ```rust
+/// use raylib_egui_rs::egui::{MemoryStorage, load_memory, save_memory};
+///
+/// let mut storage = MemoryStorage::default();
+/// let ctx = egui::Context::default();
+/// ctx.memory_mut(|memory| memory.options.zoom_factor = 1.5);
+/// save_memory(&ctx, &mut storage).unwrap();
+///
+/// let restored = egui::Context::default();
+/// assert_eq!(load_memory(&restored, &storage), Ok(true));
+/// assert_eq!(restored.memory(|memory| memory.options.zoom_factor), 1.5);
+/// ```
+pub fn load_memory(ctx: &egui::Context, storage: &dyn Storage) -> Result<bool, String> {
+    let Some(text) = storage.get_string(MEMORY_KEY) else {
+        return Ok(false);
+    };
+    let memory: egui::Memory =
+        ron::from_str(&text).map_err(|err| format!("failed to parse egui memory: {err}"))?;
+    ctx.memory_mut(|current| *current = memory);
+    Ok(true)
+}
+
+/// Log a storage error as a raylib warning, the text may quote a corrupted file.
+pub(crate) fn log_warning(text: &str) {
+    raylib::TraceLog(
+        raylib::TraceLogLevel::LOG_WARNING as i32,
+        &text.replace('\0', ""),
+    );
+}
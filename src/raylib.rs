@@ -6,10 +6,11 @@ use crate::ffi;
 use crate::math;
 
 pub use crate::ffi::{
-    AudioStream, BoundingBox, Camera, Camera2D, Camera3D, CameraMode, CameraProjection, Font,
-    Image, KeyboardKey, Material, MaterialMapIndex, Mesh, Model, ModelAnimation, MouseButton,
-    Music, NPatchInfo, PixelFormat, Ray, RayCollision, RenderTexture2D, Shader, Sound, Texture2D,
-    TextureCubemap, TextureFilter, VrDeviceInfo, VrStereoConfig, Wave, float3, float16,
+    AudioStream, BoundingBox, Camera, Camera2D, Camera3D, CameraMode, CameraProjection,
+    ConfigFlags, Font, GamepadAxis, GamepadButton, Gesture, Image, KeyboardKey, Material,
+    MaterialMapIndex, Mesh, Model, ModelAnimation, MouseButton, MouseCursor, Music, NPatchInfo,
+    PixelFormat, Ray, RayCollision, RenderTexture2D, Shader, Sound, Texture2D, TextureCubemap,
+    TextureFilter, TextureWrap, TraceLogLevel, VrDeviceInfo, VrStereoConfig, Wave, float3, float16,
 };
 
 //------------------------------------------------------------------------------------
@@ -253,10 +254,13 @@ pub fn SetClipboardText(text: &str) {
     unsafe { ffi::SetClipboardText(text.as_ptr()) }
 }
 
-/// Get clipboard text content
+/// Get clipboard text content, empty if the clipboard holds no text
 pub fn GetClipboardText() -> &'static str {
     unsafe {
         let c_str = ffi::GetClipboardText();
+        if c_str.is_null() {
+            return "";
+        }
         CStr::from_ptr(c_str)
             .to_str()
             .expect("Failed to convert C string to &str")
@@ -620,9 +624,11 @@ pub fn OpenURL(url: &str) {
 }
 
 /// Show trace log messages (a simple wrapper for the variadic C function)
+///
+/// The text is printed as is, it is never used as a format string.
 pub fn TraceLog(log_level: i32, text: &str) {
     let text_c = CString::new(text).expect("CString::new failed");
-    unsafe { ffi::TraceLog(log_level, text_c.as_ptr()) }
+    unsafe { ffi::TraceLog(log_level, c"%s".as_ptr(), text_c.as_ptr()) }
 }
 
 /// Set the current threshold (minimum) log level
@@ -710,6 +716,16 @@ pub fn SaveFileText(file_name: &str, text: &str) -> bool {
     }
 }
 
+//------------------------------------------------------------------------------------
+// File system functions
+//------------------------------------------------------------------------------------
+
+/// Check if file exists
+pub fn FileExists(file_name: &str) -> bool {
+    let file_name_c = CString::new(file_name).expect("CString::new failed");
+    unsafe { ffi::FileExists(file_name_c.as_ptr()) }
+}
+
 //------------------------------------------------------------------------------------
 // Input Handling Functions (Module: core)
 //------------------------------------------------------------------------------------
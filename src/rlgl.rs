@@ -789,6 +789,16 @@ pub fn load_vertex_buffer_element<T>(buffer: &[T], dynamic: bool) -> u32 {
     unsafe { ffi::rlLoadVertexBufferElement(buffer.as_ptr() as *const c_void, size, dynamic) }
 }
 
+/// Load an uninitialized vertex buffer object of `size` bytes, to be filled with updates
+pub fn allocate_vertex_buffer(size: usize, dynamic: bool) -> u32 {
+    unsafe { ffi::rlLoadVertexBuffer(std::ptr::null(), size as i32, dynamic) }
+}
+
+/// Load an uninitialized vertex buffer elements object of `size` bytes
+pub fn allocate_vertex_buffer_element(size: usize, dynamic: bool) -> u32 {
+    unsafe { ffi::rlLoadVertexBufferElement(std::ptr::null(), size as i32, dynamic) }
+}
+
 /// Update vertex buffer object data on GPU buffer
 pub fn update_vertex_buffer<T>(buffer_id: u32, data: &[T], offset: i32) {
     let data_size = (data.len() * std::mem::size_of::<T>()) as i32;
@@ -815,29 +825,17 @@ pub fn unload_vertex_buffer(vbo_id: u32) {
     unsafe { ffi::rlUnloadVertexBuffer(vbo_id) }
 }
 
-// TODO: / Set vertex attribute data configuration
-// pub fn set_vertex_attribute(
-//     index: u32,
-//     comp_size: i32,
-//     attrib_type: i32,
-//     normalized: bool,
-//     stride: i32,
-//     offset: i32,
-// ) {
-//     // The C API uses an integer as an offset, which can be interpreted as a pointer.
-//     // In Rust, we pass the offset directly.
-//     let ptr_offset = offset as *const c_void;
-//     unsafe {
-//         ffi::rlSetVertexAttribute(
-//             index,
-//             comp_size,
-//             attrib_type,
-//             normalized,
-//             stride,
-//             ptr_offset,
-//         )
-//     }
-// }
+/// Set vertex attribute data configuration, `offset` is in bytes into the bound buffer
+pub fn set_vertex_attribute(
+    index: u32,
+    comp_size: i32,
+    attrib_type: i32,
+    normalized: bool,
+    stride: i32,
+    offset: i32,
+) {
+    unsafe { ffi::rlSetVertexAttribute(index, comp_size, attrib_type, normalized, stride, offset) }
+}
 
 /// Set vertex attribute data divisor
 pub fn set_vertex_attribute_divisor(index: u32, divisor: i32) {